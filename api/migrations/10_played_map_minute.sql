@@ -0,0 +1,18 @@
+CREATE MATERIALIZED VIEW played_map_minute
+WITH (timescaledb.continuous, timescaledb.materialized_only = false) AS
+SELECT time_bucket(INTERVAL '1 minute', time) as time,
+  server_id, map_id, mode_id, bottom_tier, top_tier, user_id, count(*) as reports
+FROM played_map
+WHERE NOT implausible
+GROUP BY time_bucket(INTERVAL '1 minute', time),
+  server_id, map_id, mode_id, bottom_tier, top_tier, user_id
+WITH NO DATA;
+
+-- refresh at least the largest aggregation window, so late reports are picked up
+SELECT add_continuous_aggregate_policy('played_map_minute',
+  start_offset => INTERVAL '2 days',
+  end_offset => INTERVAL '1 minute',
+  schedule_interval => INTERVAL '1 minute');
+
+CREATE INDEX idx_played_map_minute_server_id_bottom_tier_top_tier_time
+  ON played_map_minute(server_id, bottom_tier, top_tier, time DESC);
\ No newline at end of file
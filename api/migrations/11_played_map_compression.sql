@@ -0,0 +1,5 @@
+ALTER TABLE played_map SET (
+  timescaledb.compress,
+  timescaledb.compress_segmentby = 'server_id',
+  timescaledb.compress_orderby = 'time DESC'
+);
\ No newline at end of file
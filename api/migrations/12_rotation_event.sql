@@ -0,0 +1,20 @@
+CREATE TABLE rotation_event (
+  id             BIGSERIAL   NOT NULL,
+  time           TIMESTAMPTZ NOT NULL DEFAULT now(),
+  server_id      SMALLINT    NOT NULL,
+  map_id         SMALLINT    NOT NULL,
+  bottom_tier    SMALLINT    NOT NULL,
+  top_tier       SMALLINT    NOT NULL,
+  kind           TEXT        NOT NULL,
+  share          FLOAT8      NOT NULL,
+  baseline_share FLOAT8      NOT NULL,
+  CONSTRAINT pk_rotation_event PRIMARY KEY (id),
+  CONSTRAINT fk_rotation_event_server_id FOREIGN KEY (server_id) REFERENCES server(id),
+  CONSTRAINT fk_rotation_event_map_id FOREIGN KEY (map_id) REFERENCES map(id),
+  CONSTRAINT chk_rotation_event_kind CHECK (kind IN ('appeared', 'disappeared', 'shifted'))
+);
+
+CREATE INDEX idx_rotation_event_server_id_map_id_time
+  ON rotation_event(server_id, map_id, bottom_tier, top_tier, time DESC);
+
+CREATE INDEX idx_rotation_event_time ON rotation_event(time DESC);
\ No newline at end of file
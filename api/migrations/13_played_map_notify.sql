@@ -0,0 +1,13 @@
+CREATE FUNCTION notify_played_map() RETURNS TRIGGER LANGUAGE plpgsql AS $$
+BEGIN
+  -- notifications with the same payload are sent once per transaction
+  PERFORM pg_notify('played_map', (SELECT name FROM server WHERE id = NEW.server_id));
+  RETURN NULL;
+END;
+$$;
+
+CREATE TRIGGER trg_played_map_notify
+  AFTER INSERT ON played_map
+  FOR EACH ROW
+  WHEN (NOT NEW.implausible)
+  EXECUTE FUNCTION notify_played_map();
\ No newline at end of file
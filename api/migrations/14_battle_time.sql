@@ -0,0 +1,11 @@
+-- start of the battle according to the client, reports may arrive well after it
+ALTER TABLE played_map ADD COLUMN battle_time TIMESTAMPTZ;
+
+ALTER TABLE quarantined_played_map ADD COLUMN battle_time TIMESTAMPTZ;
+
+-- Reports are implausible if another report of the same user ID on the same server, but of a
+-- different battle, started within `min_battle_interval`. User IDs are pseudonyms shared by all
+-- accounts with the same lower 14 bits, so accounts of one pseudonym flag each other as well.
+-- With n reporters playing concurrently on a server, about n / 16384 * 2 * min_battle_interval
+-- / battle_interval of their reports are flagged falsely, where battle_interval is the time
+-- between battles of a single account, e.g. 1000 reporters => ~3.5% at 2 and 7 minutes.
\ No newline at end of file
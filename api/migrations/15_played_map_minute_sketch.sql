@@ -0,0 +1,30 @@
+-- requires the TimescaleDB Toolkit, which the `timescale/timescaledb-ha` images ship with
+CREATE EXTENSION IF NOT EXISTS timescaledb_toolkit;
+
+-- modes enabled by the reporter at the time of the report, so aggregates need no user IDs
+ALTER TABLE played_map ADD COLUMN enabled_mode_ids SMALLINT[];
+
+-- the aggregate is recreated without user IDs, the API refreshes its history on startup
+DROP MATERIALIZED VIEW played_map_minute;
+
+-- reporters are counted with a sketch, keeping the aggregate free of user IDs,
+-- the few combinations of enabled modes hardly add any rows
+CREATE MATERIALIZED VIEW played_map_minute
+WITH (timescaledb.continuous, timescaledb.materialized_only = false) AS
+SELECT time_bucket(INTERVAL '1 minute', time) as time,
+  server_id, map_id, mode_id, bottom_tier, top_tier, enabled_mode_ids,
+  count(*) as reports, hyperloglog(4096, user_id) as reporters
+FROM played_map
+WHERE NOT implausible
+GROUP BY time_bucket(INTERVAL '1 minute', time),
+  server_id, map_id, mode_id, bottom_tier, top_tier, enabled_mode_ids
+WITH NO DATA;
+
+-- refresh at least the largest aggregation window, so late reports are picked up
+SELECT add_continuous_aggregate_policy('played_map_minute',
+  start_offset => INTERVAL '2 days',
+  end_offset => INTERVAL '1 minute',
+  schedule_interval => INTERVAL '1 minute');
+
+CREATE INDEX idx_played_map_minute_server_id_bottom_tier_top_tier_time
+  ON played_map_minute(server_id, bottom_tier, top_tier, time DESC);
\ No newline at end of file
@@ -0,0 +1,5 @@
+-- when a battle was reported, so deduplication entries can expire with the reports
+ALTER TABLE played_battle ADD COLUMN time TIMESTAMPTZ NOT NULL DEFAULT now();
+
+CREATE INDEX idx_played_battle_time
+  ON played_battle(time);
\ No newline at end of file
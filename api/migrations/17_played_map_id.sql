@@ -0,0 +1,19 @@
+-- Volatile defaults cannot be added while compression is enabled, so it is turned off until the
+-- column exists. The API adds the compression policy again on startup.
+SELECT remove_compression_policy('played_map', if_exists => true);
+
+SELECT decompress_chunk(chunk, if_compressed => true)
+FROM show_chunks('played_map') as chunk;
+
+ALTER TABLE played_map SET (
+  timescaledb.compress = false
+);
+
+-- unique tiebreaker for paginating reports
+ALTER TABLE played_map ADD COLUMN id BIGSERIAL;
+
+ALTER TABLE played_map SET (
+  timescaledb.compress,
+  timescaledb.compress_segmentby = 'server_id',
+  timescaledb.compress_orderby = 'time DESC'
+);
\ No newline at end of file
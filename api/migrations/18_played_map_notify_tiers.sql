@@ -0,0 +1,11 @@
+-- payload is `<server>:<bottom tier>:<top tier>`, so subscribers can skip other tiers
+CREATE OR REPLACE FUNCTION notify_played_map() RETURNS TRIGGER LANGUAGE plpgsql AS $$
+BEGIN
+  -- notifications with the same payload are sent once per transaction
+  PERFORM pg_notify(
+    'played_map',
+    (SELECT name FROM server WHERE id = NEW.server_id) || ':' || NEW.bottom_tier || ':' || NEW.top_tier
+  );
+  RETURN NULL;
+END;
+$$;
\ No newline at end of file
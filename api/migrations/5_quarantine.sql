@@ -0,0 +1,19 @@
+CREATE TABLE quarantined_played_map (
+  time        TIMESTAMPTZ NOT NULL DEFAULT now(),
+  user_id     TEXT        NOT NULL,
+  server      TEXT        NOT NULL,
+  map         TEXT        NOT NULL,
+  mode        TEXT        NOT NULL,
+  bottom_tier SMALLINT    NOT NULL,
+  top_tier    SMALLINT    NOT NULL
+);
+
+CREATE TABLE pending_catalog_entry (
+  entry          TEXT        NOT NULL,
+  code           TEXT        NOT NULL,
+  first_seen     TIMESTAMPTZ NOT NULL DEFAULT now(),
+  last_seen      TIMESTAMPTZ NOT NULL DEFAULT now(),
+  reporter_count INTEGER     NOT NULL DEFAULT 1,
+  CONSTRAINT pk_pending_catalog_entry PRIMARY KEY (entry, code),
+  CONSTRAINT chk_pending_catalog_entry_entry CHECK (entry IN ('server', 'map', 'mode'))
+);
\ No newline at end of file
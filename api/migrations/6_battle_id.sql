@@ -0,0 +1,14 @@
+ALTER TABLE played_map ADD COLUMN battle_id BIGINT;
+
+-- Unique indexes on a hypertable must include its time column, which would defeat
+-- deduplication of retried reports. Uniqueness is enforced by a plain table instead.
+CREATE TABLE played_battle (
+  user_id   TEXT   NOT NULL,
+  battle_id BIGINT NOT NULL,
+  CONSTRAINT pk_played_battle PRIMARY KEY (user_id, battle_id)
+);
+
+ALTER TABLE quarantined_played_map ADD COLUMN battle_id BIGINT;
+
+CREATE UNIQUE INDEX uidx_quarantined_played_map_user_id_battle_id
+  ON quarantined_played_map(user_id, battle_id);
\ No newline at end of file
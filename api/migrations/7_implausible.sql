@@ -0,0 +1,6 @@
+ALTER TABLE mode ADD COLUMN min_battle_interval INTERVAL NOT NULL DEFAULT INTERVAL '2 minutes';
+
+ALTER TABLE played_map ADD COLUMN implausible BOOLEAN NOT NULL DEFAULT false;
+
+CREATE INDEX idx_played_map_user_id_time
+  ON played_map(user_id, time DESC);
\ No newline at end of file
@@ -0,0 +1,8 @@
+CREATE TABLE blocked_map (
+  user_id       TEXT        NOT NULL,
+  map_id        SMALLINT    NOT NULL,
+  blocked_until TIMESTAMPTZ NOT NULL,
+  updated       TIMESTAMPTZ NOT NULL DEFAULT now(),
+  CONSTRAINT pk_blocked_map PRIMARY KEY (user_id, map_id),
+  CONSTRAINT fk_blocked_map_map_id FOREIGN KEY (map_id) REFERENCES map(id)
+);
\ No newline at end of file
@@ -0,0 +1,10 @@
+CREATE TABLE active_modes (
+  time     TIMESTAMPTZ NOT NULL DEFAULT now(),
+  user_id  TEXT        NOT NULL,
+  mode_ids SMALLINT[]  NOT NULL
+);
+
+SELECT create_hypertable('active_modes', 'time');
+
+CREATE INDEX idx_active_modes_user_id_time
+  ON active_modes(user_id, time DESC);
\ No newline at end of file
@@ -0,0 +1,4 @@
+SELECT
+  add_compression_policy('played_map', make_interval(days => $1)) as compression,
+  add_retention_policy('played_map', make_interval(days => $2)) as retention,
+  add_retention_policy('active_modes', make_interval(days => $2)) as active_modes_retention;
\ No newline at end of file
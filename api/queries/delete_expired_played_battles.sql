@@ -0,0 +1,2 @@
+DELETE FROM played_battle
+WHERE time < now() - make_interval(days => $1);
\ No newline at end of file
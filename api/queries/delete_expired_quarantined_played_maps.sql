@@ -0,0 +1,2 @@
+DELETE FROM quarantined_played_map
+WHERE time < now() - make_interval(days => $1);
\ No newline at end of file
@@ -0,0 +1,2 @@
+DELETE FROM pending_catalog_entry
+WHERE entry = $1 AND code = $2;
\ No newline at end of file
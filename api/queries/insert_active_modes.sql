@@ -0,0 +1,15 @@
+WITH current_active_modes AS (
+  SELECT coalesce(array_agg(id ORDER BY id), '{}') as mode_ids
+  FROM mode
+  WHERE code = ANY($2)
+)
+INSERT INTO active_modes(user_id, mode_ids)
+SELECT $1, mode_ids
+FROM current_active_modes
+WHERE mode_ids IS DISTINCT FROM (
+  SELECT mode_ids
+  FROM active_modes
+  WHERE user_id = $1
+  ORDER BY time DESC
+  LIMIT 1
+);
\ No newline at end of file
@@ -0,0 +1,6 @@
+INSERT INTO blocked_map(user_id, map_id, blocked_until)
+SELECT $1, map.id, blocked.blocked_until
+FROM unnest($2::TEXT[], $3::TIMESTAMPTZ[]) AS blocked(map, blocked_until)
+  INNER JOIN map ON blocked.map = map.code
+WHERE blocked.blocked_until > now()
+ON CONFLICT DO NOTHING;
\ No newline at end of file
@@ -0,0 +1,4 @@
+INSERT INTO mode(id, code)
+VALUES ($1, $2)
+ON CONFLICT DO NOTHING
+RETURNING id;
\ No newline at end of file
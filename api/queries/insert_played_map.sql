@@ -1,5 +1,37 @@
-INSERT INTO played_map(user_id, server_id, map_id, mode_id, bottom_tier, top_tier)
-SELECT $1, server.id, map.id, mode.id, $5, $6
-FROM server, map, mode
-WHERE server.name = $2 AND map.code = $3 AND mode.code = $4
-RETURNING played_map.time;
\ No newline at end of file
+WITH report AS (
+  SELECT server.id as server_id, map.id as map_id, mode.id as mode_id
+  FROM server, map, mode
+  WHERE server.name = $2 AND map.code = $3 AND mode.code = $4
+), battle AS (
+  INSERT INTO played_battle(user_id, battle_id)
+  SELECT $1, $7 FROM report WHERE $7::BIGINT IS NOT NULL
+  ON CONFLICT DO NOTHING
+  RETURNING battle_id
+), reporter_active_modes AS (
+  SELECT mode_ids
+  FROM active_modes
+  WHERE user_id = $1
+  ORDER BY time DESC
+  LIMIT 1
+), reported_battle AS (
+  SELECT coalesce($8, now()) as time
+), conflicting_played_map AS (
+  SELECT FROM played_map
+    INNER JOIN mode ON played_map.mode_id = mode.id,
+    report, reported_battle
+  WHERE played_map.user_id = $1
+    AND played_map.server_id = report.server_id
+    AND NOT coalesce(played_map.battle_id = $7, false)
+    AND played_map.time > now() - make_interval(mins => $9) - (SELECT max(min_battle_interval) FROM mode)
+    AND coalesce(played_map.battle_time, played_map.time) > reported_battle.time - mode.min_battle_interval
+    AND coalesce(played_map.battle_time, played_map.time) < reported_battle.time + mode.min_battle_interval
+)
+INSERT INTO played_map(user_id, server_id, map_id, mode_id, bottom_tier, top_tier, battle_id, battle_time,
+  enabled_mode_ids, implausible)
+SELECT $1, server_id, map_id, mode_id, $5, $6, $7, $8,
+  (SELECT mode_ids FROM reporter_active_modes),
+  reported_battle.time NOT BETWEEN now() - make_interval(mins => $9) AND now() + INTERVAL '1 minute'
+    OR EXISTS(SELECT FROM conflicting_played_map)
+FROM report, reported_battle
+WHERE $7::BIGINT IS NULL OR EXISTS(SELECT FROM battle)
+RETURNING played_map.time, played_map.implausible;
\ No newline at end of file
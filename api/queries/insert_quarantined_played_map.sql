@@ -0,0 +1,3 @@
+INSERT INTO quarantined_played_map(user_id, server, map, mode, bottom_tier, top_tier, battle_id, battle_time)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+ON CONFLICT DO NOTHING;
\ No newline at end of file
@@ -0,0 +1,79 @@
+WITH current_map AS (
+  SELECT server_id, bottom_tier, top_tier, map_id, sum(reports) as count
+  FROM played_map_minute
+  WHERE time > now() - make_interval(mins => $1)
+  GROUP BY server_id, bottom_tier, top_tier, map_id
+), baseline_map AS (
+  SELECT server_id, bottom_tier, top_tier, map_id, sum(reports) as count
+  FROM played_map_minute
+  WHERE time > now() - make_interval(mins => $1 + $2)
+    AND time <= now() - make_interval(mins => $1)
+  GROUP BY server_id, bottom_tier, top_tier, map_id
+), compared_map AS (
+  SELECT coalesce(current_map.server_id, baseline_map.server_id) as server_id,
+    coalesce(current_map.bottom_tier, baseline_map.bottom_tier) as bottom_tier,
+    coalesce(current_map.top_tier, baseline_map.top_tier) as top_tier,
+    coalesce(current_map.map_id, baseline_map.map_id) as map_id,
+    coalesce(current_map.count, 0)::FLOAT8 as count,
+    coalesce(baseline_map.count, 0)::FLOAT8 as baseline_count
+  FROM current_map
+    FULL OUTER JOIN baseline_map
+      ON current_map.server_id = baseline_map.server_id
+      AND current_map.bottom_tier = baseline_map.bottom_tier
+      AND current_map.top_tier = baseline_map.top_tier
+      AND current_map.map_id = baseline_map.map_id
+), bracket AS (
+  SELECT server_id, bottom_tier, top_tier,
+    sum(count) as count, sum(baseline_count) as baseline_count
+  FROM compared_map
+  GROUP BY server_id, bottom_tier, top_tier
+), scored_map AS (
+  SELECT compared_map.server_id, compared_map.bottom_tier, compared_map.top_tier,
+    compared_map.map_id,
+    compared_map.count / bracket.count as share,
+    compared_map.baseline_count / bracket.baseline_count as baseline_share,
+    (compared_map.count + compared_map.baseline_count)
+      / (bracket.count + bracket.baseline_count) as pooled_share,
+    bracket.count as total,
+    bracket.baseline_count as baseline_total
+  FROM compared_map
+    INNER JOIN bracket
+      ON compared_map.server_id = bracket.server_id
+      AND compared_map.bottom_tier = bracket.bottom_tier
+      AND compared_map.top_tier = bracket.top_tier
+  WHERE bracket.count >= $3
+    AND bracket.baseline_count >= $3
+), detected_event AS (
+  SELECT server_id, map_id, bottom_tier, top_tier,
+    CASE
+      WHEN baseline_share = 0 THEN 'appeared'
+      WHEN share = 0 THEN 'disappeared'
+      ELSE 'shifted'
+    END as kind,
+    share, baseline_share
+  FROM scored_map
+  -- two-proportion z-test
+  WHERE abs(share - baseline_share)
+    > $4 * sqrt(pooled_share * (1 - pooled_share) * (1 / total + 1 / baseline_total))
+)
+INSERT INTO rotation_event (server_id, map_id, bottom_tier, top_tier, kind, share, baseline_share)
+SELECT detected_event.server_id, detected_event.map_id, detected_event.bottom_tier,
+  detected_event.top_tier, detected_event.kind, detected_event.share,
+  detected_event.baseline_share
+FROM detected_event
+  LEFT JOIN LATERAL (
+    SELECT kind, share, baseline_share
+    FROM rotation_event
+    WHERE rotation_event.server_id = detected_event.server_id
+      AND rotation_event.map_id = detected_event.map_id
+      AND rotation_event.bottom_tier = detected_event.bottom_tier
+      AND rotation_event.top_tier = detected_event.top_tier
+      AND rotation_event.time > now() - make_interval(mins => $1 + $2)
+    ORDER BY rotation_event.time DESC
+    LIMIT 1
+  ) as latest_event ON TRUE
+-- the same change is detected until it is part of the baseline, record it only once
+WHERE latest_event.kind IS DISTINCT FROM detected_event.kind
+  OR sign(latest_event.share - latest_event.baseline_share)
+    <> sign(detected_event.share - detected_event.baseline_share)
+RETURNING id;
\ No newline at end of file
@@ -0,0 +1,4 @@
+INSERT INTO server(id, name, region)
+VALUES ($1, $2, $3)
+ON CONFLICT DO NOTHING
+RETURNING id;
\ No newline at end of file
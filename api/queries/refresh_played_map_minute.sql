@@ -0,0 +1 @@
+CALL refresh_continuous_aggregate('played_map_minute', $1::TIMESTAMPTZ, NULL);
\ No newline at end of file
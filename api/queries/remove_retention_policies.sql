@@ -0,0 +1,4 @@
+SELECT
+  remove_compression_policy('played_map', if_exists => true) as compression,
+  remove_retention_policy('played_map', if_exists => true) as retention,
+  remove_retention_policy('active_modes', if_exists => true) as active_modes_retention;
\ No newline at end of file
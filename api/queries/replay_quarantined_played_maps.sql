@@ -0,0 +1,37 @@
+WITH resolved AS (
+  DELETE FROM quarantined_played_map
+  USING server, map, mode
+  WHERE server.name = quarantined_played_map.server
+    AND map.code = quarantined_played_map.map
+    AND mode.code = quarantined_played_map.mode
+  RETURNING quarantined_played_map.time, quarantined_played_map.user_id,
+    server.id as server_id, map.id as map_id, mode.id as mode_id,
+    quarantined_played_map.bottom_tier, quarantined_played_map.top_tier,
+    quarantined_played_map.battle_id, quarantined_played_map.battle_time
+), battle AS (
+  INSERT INTO played_battle(user_id, battle_id)
+  SELECT user_id, battle_id FROM resolved WHERE battle_id IS NOT NULL
+  ON CONFLICT DO NOTHING
+  RETURNING user_id, battle_id
+)
+INSERT INTO played_map(time, user_id, server_id, map_id, mode_id, bottom_tier, top_tier, battle_id, battle_time,
+  enabled_mode_ids)
+SELECT resolved.time, resolved.user_id, resolved.server_id, resolved.map_id, resolved.mode_id,
+  resolved.bottom_tier, resolved.top_tier, resolved.battle_id, resolved.battle_time,
+  reporter_active_modes.mode_ids
+FROM resolved
+  LEFT JOIN LATERAL (
+    SELECT mode_ids
+    FROM active_modes
+    WHERE active_modes.user_id = resolved.user_id
+      AND active_modes.time <= resolved.time
+    ORDER BY active_modes.time DESC
+    LIMIT 1
+  ) as reporter_active_modes ON TRUE
+-- battles which were reported again in the meantime are already in `played_map`
+WHERE resolved.battle_id IS NULL
+  OR EXISTS(
+    SELECT FROM battle
+    WHERE battle.user_id = resolved.user_id AND battle.battle_id = resolved.battle_id
+  )
+RETURNING played_map.time;
\ No newline at end of file
@@ -0,0 +1,19 @@
+WITH reported_map AS (
+  SELECT map_id, reports, reporters
+  FROM played_map_minute
+  WHERE server_id = $1
+    AND $2 <= top_tier
+    AND bottom_tier <= $3
+    AND time > now() - make_interval(mins => $4)
+), coverage AS (
+  SELECT coalesce(distinct_count(rollup(reporters)), 0) as reporters,
+    coalesce(sum(reports), 0)::BIGINT as reports,
+    count(DISTINCT map_id) as reported_maps
+  FROM reported_map
+)
+SELECT map.code as "map?", coverage.reporters as "reporters!", coverage.reports as "reports!",
+  coverage.reported_maps as "reported_maps!"
+FROM coverage
+  LEFT JOIN map
+    ON NOT EXISTS (SELECT FROM reported_map WHERE reported_map.map_id = map.id)
+ORDER BY map.code;
\ No newline at end of file
@@ -0,0 +1,19 @@
+WITH reporter AS (
+  SELECT DISTINCT user_id
+  FROM played_map
+  WHERE server_id = (SELECT id FROM server WHERE name = $1)
+    AND $2 <= top_tier
+    AND bottom_tier <= $3
+    AND time > now() - INTERVAL '1 day'
+    AND NOT implausible
+), current_blocked_map AS (
+  SELECT map_id, count(*) as count
+  FROM blocked_map
+    INNER JOIN reporter ON blocked_map.user_id = reporter.user_id
+  WHERE blocked_until > now()
+  GROUP BY map_id
+)
+SELECT map.code as map, current_blocked_map.count
+FROM current_blocked_map
+  INNER JOIN map ON current_blocked_map.map_id = map.id
+ORDER BY current_blocked_map.count DESC;
\ No newline at end of file
@@ -1,14 +1,59 @@
-WITH current_map AS (
-  SELECT map_id, mode_id, count(DISTINCT user_id) as count
-  FROM played_map
-  WHERE server_id = (SELECT id FROM server WHERE name = $1)
+WITH reported_map AS (
+  SELECT map_id, mode_id, enabled_mode_ids, reporters
+  FROM played_map_minute
+  WHERE server_id IN (SELECT id FROM server WHERE name = ANY($1) OR region = $7)
     AND $2 <= top_tier
     AND bottom_tier <= $3
-    AND time > now() - INTERVAL '1 hour'
+    AND time > now() - make_interval(mins => $4)
+), current_map AS (
+  SELECT map_id, mode_id, distinct_count(rollup(reporters)) as count
+  FROM reported_map
   GROUP BY map_id, mode_id
+), historical_map AS (
+  SELECT map_id, mode_id, sum(reports)::BIGINT as count
+  FROM played_map_minute
+  WHERE server_id IN (SELECT id FROM server WHERE name = ANY($1) OR region = $7)
+    AND $2 <= top_tier
+    AND bottom_tier <= $3
+    AND time > now() - make_interval(mins => $5)
+  GROUP BY map_id, mode_id
+), estimated_map AS (
+  SELECT coalesce(current_map.map_id, historical_map.map_id) as map_id,
+    coalesce(current_map.mode_id, historical_map.mode_id) as mode_id,
+    coalesce(current_map.count, 0) as count,
+    coalesce(historical_map.count, 0) as historical_count
+  FROM current_map
+    FULL OUTER JOIN historical_map
+      ON current_map.map_id = historical_map.map_id
+      AND current_map.mode_id = historical_map.mode_id
+), total AS (
+  SELECT sum(count)::FLOAT8 as count, sum(historical_count)::FLOAT8 as historical_count
+  FROM estimated_map
+), reporter AS (
+  SELECT coalesce(distinct_count(rollup(reporters)), 0) as count
+  FROM reported_map
+), enabled_mode AS (
+  -- reporters of a mode have it enabled, even if their enabled modes are unknown
+  SELECT mode.id as mode_id, distinct_count(rollup(reported_map.reporters)) as count
+  FROM mode
+    INNER JOIN reported_map
+      ON reported_map.mode_id = mode.id
+      OR mode.id = ANY(reported_map.enabled_mode_ids)
+  GROUP BY mode.id
 )
-SELECT map.code as map, mode.code as mode, current_map.count
-FROM current_map
-  INNER JOIN mode ON current_map.mode_id = mode.id
-  INNER JOIN map ON current_map.map_id = map.id
-ORDER BY current_map.count DESC;
+SELECT map.code as map, mode.code as mode, estimated_map.count,
+  estimated_map.count::FLOAT8 / enabled_mode.count as normalized,
+  CASE
+    WHEN total.historical_count > 0 THEN
+      (estimated_map.count + $6::FLOAT8 * estimated_map.historical_count / total.historical_count)
+        / (total.count + $6)
+    ELSE estimated_map.count / total.count
+  END as probability,
+  reporter.count as reporters
+FROM estimated_map
+  CROSS JOIN total
+  CROSS JOIN reporter
+  LEFT JOIN enabled_mode ON estimated_map.mode_id = enabled_mode.mode_id
+  INNER JOIN mode ON estimated_map.mode_id = mode.id
+  INNER JOIN map ON estimated_map.map_id = map.id
+ORDER BY estimated_map.count DESC, probability DESC;
\ No newline at end of file
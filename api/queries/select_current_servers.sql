@@ -1,7 +1,7 @@
 WITH current_server AS (
-  SELECT server_id, count(DISTINCT user_id) as count
-  FROM played_map
-  WHERE time > now() - INTERVAL '1 hour'
+  SELECT server_id, distinct_count(rollup(reporters)) as count
+  FROM played_map_minute
+  WHERE time > now() - make_interval(mins => $1)
   GROUP BY server_id
 )
 SELECT server.name, server.region, current_server.count
@@ -0,0 +1 @@
+SELECT time_bucket(make_interval(mins => $1), now()) as "to!";
\ No newline at end of file
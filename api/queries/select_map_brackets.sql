@@ -0,0 +1,12 @@
+WITH bracket_map AS (
+  SELECT map_id, bottom_tier, top_tier, distinct_count(rollup(reporters)) as count
+  FROM played_map_minute
+  WHERE server_id = (SELECT id FROM server WHERE name = $1)
+    AND time > now() - make_interval(mins => $2)
+  GROUP BY map_id, bottom_tier, top_tier
+)
+SELECT map.code as map, bracket_map.bottom_tier as "bottom_tier!",
+  bracket_map.top_tier as "top_tier!", bracket_map.count
+FROM bracket_map
+  INNER JOIN map ON bracket_map.map_id = map.id
+ORDER BY map.code;
\ No newline at end of file
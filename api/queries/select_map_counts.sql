@@ -0,0 +1,24 @@
+WITH bucketed_map AS (
+  SELECT time_bucket(make_interval(mins => $1), time) as bucket, server_id,
+    bottom_tier, top_tier, map_id, mode_id,
+    sum(reports) as count, distinct_count(rollup(reporters)) as reporters
+  FROM played_map_minute
+  WHERE ($2::TEXT IS NULL OR server_id = (SELECT id FROM server WHERE name = $2))
+    AND $3 <= top_tier
+    AND bottom_tier <= $4
+    AND $5 <= time
+    AND time < $6
+  GROUP BY bucket, server_id, bottom_tier, top_tier, map_id, mode_id
+  -- buckets of few reporters could single out individuals
+  HAVING distinct_count(rollup(reporters)) >= $7
+)
+SELECT bucketed_map.bucket as "time!", server.name as server,
+  bucketed_map.bottom_tier as "bottom_tier!", bucketed_map.top_tier as "top_tier!",
+  map.code as map, mode.code as mode,
+  bucketed_map.count::BIGINT as "count!", bucketed_map.reporters as "reporters!"
+FROM bucketed_map
+  INNER JOIN server ON bucketed_map.server_id = server.id
+  INNER JOIN map ON bucketed_map.map_id = map.id
+  INNER JOIN mode ON bucketed_map.mode_id = mode.id
+ORDER BY bucketed_map.bucket, server.name, bucketed_map.bottom_tier, bucketed_map.top_tier,
+  map.code, mode.code;
\ No newline at end of file
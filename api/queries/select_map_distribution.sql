@@ -0,0 +1,19 @@
+WITH reported_map AS (
+  SELECT time AT TIME ZONE $4 as local_time, map_id, reports
+  FROM played_map_minute
+  WHERE server_id = (SELECT id FROM server WHERE name = $1)
+    AND $2 <= top_tier
+    AND bottom_tier <= $3
+    AND $5 <= time
+    AND time < $6
+), slot_map AS (
+  SELECT CASE WHEN $7 THEN extract(isodow FROM local_time)::SMALLINT END as weekday,
+    CASE WHEN $8 THEN extract(hour FROM local_time)::SMALLINT END as hour,
+    map_id, sum(reports) as count
+  FROM reported_map
+  GROUP BY weekday, hour, map_id
+)
+SELECT slot_map.weekday, slot_map.hour, map.code as map, slot_map.count::BIGINT as "count!"
+FROM slot_map
+  INNER JOIN map ON slot_map.map_id = map.id
+ORDER BY slot_map.weekday, slot_map.hour, slot_map.count DESC, map.code;
\ No newline at end of file
@@ -0,0 +1,16 @@
+WITH bucketed_map AS (
+  SELECT time_bucket(make_interval(mins => $4), time) as bucket, map_id, mode_id,
+    distinct_count(rollup(reporters)) as count
+  FROM played_map_minute
+  WHERE server_id = (SELECT id FROM server WHERE name = $1)
+    AND $2 <= top_tier
+    AND bottom_tier <= $3
+    AND $5 <= time
+    AND time < $6
+  GROUP BY bucket, map_id, mode_id
+)
+SELECT bucketed_map.bucket as "time!", map.code as map, mode.code as mode, bucketed_map.count
+FROM bucketed_map
+  INNER JOIN mode ON bucketed_map.mode_id = mode.id
+  INNER JOIN map ON bucketed_map.map_id = map.id
+ORDER BY map.code, mode.code, bucketed_map.bucket;
\ No newline at end of file
@@ -0,0 +1,17 @@
+WITH observed_map AS (
+  SELECT server_id, map_id, bottom_tier, top_tier,
+    min(time) as first_seen, max(time) as last_seen, sum(reports) as reports
+  FROM played_map_minute
+  WHERE ($1::TEXT IS NULL OR server_id = (SELECT id FROM server WHERE name = $1))
+    AND time > now() - make_interval(days => $2)
+  GROUP BY server_id, map_id, bottom_tier, top_tier
+)
+SELECT server.name as server, tier::SMALLINT as "tier!", map.code as map,
+  min(observed_map.first_seen) as "first_seen!", max(observed_map.last_seen) as "last_seen!",
+  sum(observed_map.reports)::BIGINT as "reports!"
+FROM observed_map
+  CROSS JOIN LATERAL generate_series(observed_map.bottom_tier::INT, observed_map.top_tier) as tier
+  INNER JOIN server ON observed_map.server_id = server.id
+  INNER JOIN map ON observed_map.map_id = map.id
+GROUP BY server.name, tier, map.code
+ORDER BY server.name, tier, map.code;
\ No newline at end of file
@@ -0,0 +1,21 @@
+WITH enabled_mode_report AS (
+  SELECT enabled_mode_ids, mode_id, sum(reports) as count
+  FROM played_map_minute
+  WHERE server_id = (SELECT id FROM server WHERE name = $1)
+    AND $2 <= top_tier
+    AND bottom_tier <= $3
+    AND $4 <= time
+    AND time < $5
+  GROUP BY enabled_mode_ids, mode_id
+)
+SELECT CASE
+    WHEN enabled_mode_report.enabled_mode_ids IS NOT NULL THEN (
+      SELECT coalesce(array_agg(enabled_mode.code ORDER BY enabled_mode.id), '{}')
+      FROM mode as enabled_mode
+      WHERE enabled_mode.id = ANY(enabled_mode_report.enabled_mode_ids)
+    )
+  END as enabled_modes,
+  mode.code as mode, enabled_mode_report.count::BIGINT as "count!"
+FROM enabled_mode_report
+  INNER JOIN mode ON enabled_mode_report.mode_id = mode.id
+ORDER BY enabled_mode_report.count DESC;
\ No newline at end of file
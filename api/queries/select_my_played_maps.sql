@@ -0,0 +1,13 @@
+SELECT played_map.id, played_map.time, server.name as server, map.code as map, mode.code as mode,
+  played_map.bottom_tier, played_map.top_tier, played_map.implausible
+FROM played_map
+  INNER JOIN server ON played_map.server_id = server.id
+  INNER JOIN map ON played_map.map_id = map.id
+  INNER JOIN mode ON played_map.mode_id = mode.id
+WHERE played_map.user_id = $1
+  AND ($2::TEXT IS NULL OR server.name = $2)
+  AND ($3::TIMESTAMPTZ IS NULL OR $3 <= played_map.time)
+  AND ($4::TIMESTAMPTZ IS NULL OR played_map.time < $4)
+  AND ($5::TIMESTAMPTZ IS NULL OR (played_map.time, played_map.id) < ($5, $6::BIGINT))
+ORDER BY played_map.time DESC, played_map.id DESC
+LIMIT $7;
\ No newline at end of file
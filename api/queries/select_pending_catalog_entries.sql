@@ -0,0 +1,3 @@
+SELECT entry, code, first_seen, last_seen, reporter_count
+FROM pending_catalog_entry
+ORDER BY last_seen DESC;
\ No newline at end of file
@@ -0,0 +1,4 @@
+SELECT
+  EXISTS(SELECT FROM server WHERE name = $1) as "server!",
+  EXISTS(SELECT FROM map WHERE code = $2) as "map!",
+  EXISTS(SELECT FROM mode WHERE code = $3) as "mode!";
\ No newline at end of file
@@ -0,0 +1 @@
+SELECT FROM pg_advisory_xact_lock(hashtext('retention_policy'));
\ No newline at end of file
@@ -0,0 +1,12 @@
+SELECT rotation_event.time, server.name as server, map.code as map,
+  rotation_event.bottom_tier, rotation_event.top_tier, rotation_event.kind,
+  rotation_event.share, rotation_event.baseline_share
+FROM rotation_event
+  INNER JOIN server ON rotation_event.server_id = server.id
+  INNER JOIN map ON rotation_event.map_id = map.id
+WHERE ($1::TEXT IS NULL OR server.name = $1)
+  AND ($2::TEXT IS NULL OR rotation_event.kind = $2)
+  AND ($3::TIMESTAMPTZ IS NULL OR $3 <= rotation_event.time)
+  AND ($4::TIMESTAMPTZ IS NULL OR rotation_event.time < $4)
+ORDER BY rotation_event.time DESC
+LIMIT $5;
\ No newline at end of file
@@ -0,0 +1 @@
+SELECT id FROM server WHERE name = $1;
\ No newline at end of file
@@ -0,0 +1,4 @@
+SELECT name
+FROM server
+WHERE name = ANY($1) OR region = $2
+ORDER BY name;
\ No newline at end of file
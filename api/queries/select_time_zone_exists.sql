@@ -0,0 +1 @@
+SELECT EXISTS (SELECT FROM pg_timezone_names WHERE name = $1) as "exists!";
\ No newline at end of file
@@ -0,0 +1,13 @@
+INSERT INTO pending_catalog_entry AS pending(entry, code)
+VALUES ($1, $2)
+ON CONFLICT (entry, code) DO UPDATE
+SET last_seen = now(),
+    reporter_count = (
+      SELECT count(DISTINCT user_id)
+      FROM quarantined_played_map
+      WHERE pending.code = CASE pending.entry
+        WHEN 'server' THEN server
+        WHEN 'map' THEN map
+        WHEN 'mode' THEN mode
+      END
+    );
\ No newline at end of file
@@ -0,0 +1,16 @@
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+use crate::error::Result;
+
+// the refresh policy only covers recent buckets, older reports are materialized explicitly,
+// refreshing must not happen inside a transaction
+pub async fn refresh_played_map_minute(pool: &PgPool, from: DateTime<Utc>) -> Result<()> {
+    sqlx::query_file!("queries/refresh_played_map_minute.sql", from)
+        .execute(pool)
+        .await
+        .with_context(|| format!("Failed to refresh played map minutes from {}", from))?;
+
+    Ok(())
+}
@@ -1,18 +1,21 @@
 use anyhow::Context;
 use axum::async_trait;
-use axum::extract::{FromRequestParts, State};
+use axum::extract::{FromRef, FromRequestParts, State};
 use axum::http::header::AUTHORIZATION;
 use axum::http::request::Parts;
-use axum::http::{HeaderMap, Request};
+use axum::http::{HeaderMap, HeaderName, Request};
 use axum::middleware::Next;
 use axum::response::Response;
 use chrono::serde::ts_seconds;
 use chrono::{DateTime, Duration, Utc};
 use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
 
 use crate::error::{ClientError, Error, Result};
-use crate::ServerSecret;
+use crate::{AdminToken, ServerSecret};
+
+pub static X_ADMIN_TOKEN: HeaderName = HeaderName::from_static("x-admin-token");
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TokenClaims {
@@ -33,6 +36,32 @@ impl<S: Send + Sync> FromRequestParts<S> for TokenClaims {
     }
 }
 
+pub struct Admin;
+
+#[async_trait]
+impl<S> FromRequestParts<S> for Admin
+where
+    AdminToken: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let AdminToken(admin_token) = AdminToken::from_ref(state);
+        let header = parts.headers.get(&X_ADMIN_TOKEN);
+
+        match (admin_token, header) {
+            // constant time, so the token cannot be guessed from response times
+            (Some(admin_token), Some(header))
+                if bool::from(header.as_bytes().ct_eq(admin_token.as_bytes())) =>
+            {
+                Ok(Admin)
+            }
+            _ => Err(ClientError::AdminRequired.into()),
+        }
+    }
+}
+
 pub fn create_token(account_id: u64, secret: &ServerSecret) -> Result<String> {
     let claims = TokenClaims {
         exp: Utc::now() + Duration::days(30),
@@ -0,0 +1,178 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Context;
+use axum::response::sse::Event;
+use sqlx::PgPool;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{broadcast, watch};
+use tracing::error;
+
+use crate::error::{Error, Result};
+use crate::model::{
+    CurrentMapRow, CurrentMaps, GetCurrentMapsQuery, CURRENT_MAPS_STREAM_THROTTLE_SECONDS,
+    DEFAULT_WINDOW, PRIOR_WEIGHT, PRIOR_WINDOW,
+};
+use crate::notify::{PlayedMapNotification, PlayedMapNotifier};
+
+type CurrentMapsSender = Arc<watch::Sender<Option<Event>>>;
+
+// computes current maps once per distinct query, no matter how many clients stream them
+#[derive(Clone)]
+pub struct CurrentMapsHub {
+    pool: PgPool,
+    notifier: PlayedMapNotifier,
+    senders: Arc<Mutex<HashMap<GetCurrentMapsQuery, CurrentMapsSender>>>,
+}
+
+impl CurrentMapsHub {
+    pub fn new(pool: PgPool, notifier: PlayedMapNotifier) -> Self {
+        Self {
+            pool,
+            notifier,
+            senders: Default::default(),
+        }
+    }
+
+    // holds `None` until current maps are selected for the first time
+    pub fn subscribe(&self, query: &GetCurrentMapsQuery) -> watch::Receiver<Option<Event>> {
+        let query = query.normalized();
+        let mut senders = self.senders.lock().unwrap();
+        if let Some(sender) = senders.get(&query) {
+            return sender.subscribe();
+        }
+
+        let (sender, receiver) = watch::channel(None);
+        let sender = Arc::new(sender);
+        senders.insert(query.clone(), sender.clone());
+        tokio::spawn(self.clone().publish_current_maps(query, sender));
+        receiver
+    }
+
+    async fn publish_current_maps(self, query: GetCurrentMapsQuery, sender: CurrentMapsSender) {
+        // subscribe first, so no reports are missed in between
+        let mut receiver = self.notifier.subscribe();
+
+        let names = match select_server_names(&self.pool, &query).await {
+            Ok(names) => names,
+            Err(e) => {
+                error!("{:?}", e);
+                // dropping the sender ends the streams, clients reconnect on their own
+                self.senders.lock().unwrap().remove(&query);
+                return;
+            }
+        };
+
+        loop {
+            match select_current_maps_event(&self.pool, &query).await {
+                Ok(event) => {
+                    sender.send_replace(Some(event));
+                }
+                Err(e) => error!("{:?}", e),
+            }
+
+            tokio::select! {
+                received = recv_reports(&mut receiver, &names, &query) => {
+                    if !received {
+                        self.senders.lock().unwrap().remove(&query);
+                        return;
+                    }
+                }
+                _ = sender.closed() => {
+                    if self.remove_unsubscribed(&query) {
+                        return;
+                    }
+                }
+            }
+
+            // coalesce bursts of reports into one update
+            tokio::time::sleep(Duration::from_secs(CURRENT_MAPS_STREAM_THROTTLE_SECONDS)).await;
+            while receiver.try_recv().is_ok() {}
+        }
+    }
+
+    // clients may have subscribed again since the last one left
+    fn remove_unsubscribed(&self, query: &GetCurrentMapsQuery) -> bool {
+        let mut senders = self.senders.lock().unwrap();
+        match senders.get(query) {
+            Some(sender) if sender.receiver_count() > 0 => false,
+            _ => {
+                senders.remove(query);
+                true
+            }
+        }
+    }
+}
+
+// waits for reports of the given servers and tiers, returns false once no more reports arrive
+async fn recv_reports(
+    receiver: &mut broadcast::Receiver<PlayedMapNotification>,
+    names: &HashSet<String>,
+    query: &GetCurrentMapsQuery,
+) -> bool {
+    loop {
+        match receiver.recv().await {
+            Ok(played_map)
+                if names.contains(&played_map.server)
+                    && played_map.bottom_tier <= query.max_tier
+                    && query.min_tier <= played_map.top_tier =>
+            {
+                return true
+            }
+            Ok(_) => continue,
+            Err(RecvError::Lagged(_)) => return true,
+            Err(RecvError::Closed) => return false,
+        }
+    }
+}
+
+async fn select_server_names(
+    pool: &PgPool,
+    query: &GetCurrentMapsQuery,
+) -> Result<HashSet<String>> {
+    let names = sqlx::query_file_scalar!(
+        "queries/select_server_names.sql",
+        &query.servers(),
+        query.region
+    )
+    .fetch_all(pool)
+    .await
+    .with_context(|| format!("Failed to select server names: {:?}", query))?;
+
+    Ok(names.into_iter().collect())
+}
+
+async fn select_current_maps_event(pool: &PgPool, query: &GetCurrentMapsQuery) -> Result<Event> {
+    let current_maps =
+        select_current_maps(pool, &query.servers(), query.region.as_deref(), query).await?;
+
+    Event::default()
+        .json_data(current_maps)
+        .context("Failed to serialize current maps")
+        .map_err(Error::from)
+}
+
+pub async fn select_current_maps(
+    pool: &PgPool,
+    servers: &[String],
+    region: Option<&str>,
+    query: &GetCurrentMapsQuery,
+) -> Result<CurrentMaps> {
+    let rows = sqlx::query_file_as!(
+        CurrentMapRow,
+        "queries/select_current_maps.sql",
+        servers,
+        query.min_tier,
+        query.max_tier,
+        query.window.unwrap_or(DEFAULT_WINDOW),
+        PRIOR_WINDOW,
+        PRIOR_WEIGHT,
+        region
+    )
+    .fetch_all(pool)
+    .await
+    .with_context(|| format!("Failed to select current maps: {:?}", query))?;
+
+    Ok(CurrentMaps::from_rows(rows))
+}
@@ -0,0 +1,105 @@
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::Context;
+use chrono::{DateTime, Duration, Utc};
+use futures::TryStreamExt;
+use serde::Serialize;
+use sqlx::PgPool;
+use tracing::info;
+
+use crate::error::Result;
+use crate::export::{Encoder, ExportFormat, CHUNK_SIZE};
+use crate::model::{ExportMapCountsQuery, MapCountRow, MIN_EXPORT_REPORTERS};
+use crate::util;
+
+#[derive(Debug, Serialize)]
+struct DatasetMetadata {
+    generated: DateTime<Utc>,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    bucket_minutes: i32,
+    min_reporters: i64,
+    rows: u64,
+}
+
+// user IDs are not part of the dataset, only the number of distinct reporters per bucket
+pub async fn dump_dataset(pool: &PgPool, directory: &Path) -> Result<()> {
+    let bucket_minutes = util::env_var_or("DUMP_BUCKET_MINUTES", 60)?;
+    let min_reporters = util::env_var_or("DUMP_MIN_REPORTERS", MIN_EXPORT_REPORTERS)?;
+    let days = util::env_var_or("DUMP_DAYS", 90)?;
+
+    // the current bucket is left out, as it is incomplete
+    let to = sqlx::query_file_scalar!("queries/select_dataset_range.sql", bucket_minutes)
+        .fetch_one(pool)
+        .await
+        .context("Failed to select dataset range")?;
+    let from = to - Duration::days(days);
+
+    fs::create_dir_all(directory)
+        .with_context(|| format!("Failed to create directory {}", directory.display()))?;
+
+    let map_counts_path = directory.join("map_counts.csv");
+    let mut map_counts_file = File::create(&map_counts_path)
+        .with_context(|| format!("Failed to create {}", map_counts_path.display()))?;
+
+    let query = ExportMapCountsQuery {
+        server: None,
+        min_tier: None,
+        max_tier: None,
+        bucket: Some(bucket_minutes),
+        from: Some(from),
+        to: Some(to),
+    };
+    let mut rows = sqlx::query_file_as!(
+        MapCountRow,
+        "queries/select_map_counts.sql",
+        query.bucket(),
+        query.server,
+        query.min_tier(),
+        query.max_tier(),
+        query.from(),
+        query.to(),
+        min_reporters
+    )
+    .fetch(pool);
+
+    let mut encoder = Encoder::new(ExportFormat::Csv);
+    let mut row_count = 0;
+    while let Some(row) = rows
+        .try_next()
+        .await
+        .context("Failed to fetch dataset row")?
+    {
+        encoder.encode(&row)?;
+        if let Some(chunk) = encoder.take_chunk(CHUNK_SIZE) {
+            map_counts_file
+                .write_all(&chunk)
+                .context("Failed to write dataset rows")?;
+        }
+        row_count += 1;
+    }
+    if let Some(chunk) = encoder.take_chunk(0) {
+        map_counts_file
+            .write_all(&chunk)
+            .context("Failed to write dataset rows")?;
+    }
+
+    let metadata = DatasetMetadata {
+        generated: Utc::now(),
+        from,
+        to,
+        bucket_minutes,
+        min_reporters,
+        rows: row_count,
+    };
+    let metadata_path = directory.join("metadata.json");
+    let metadata_file = File::create(&metadata_path)
+        .with_context(|| format!("Failed to create {}", metadata_path.display()))?;
+    serde_json::to_writer_pretty(metadata_file, &metadata)
+        .context("Failed to write dataset metadata")?;
+
+    info!("Dumped {} rows to {}.", row_count, directory.display());
+    Ok(())
+}
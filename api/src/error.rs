@@ -1,3 +1,4 @@
+use axum::http::header::RETRY_AFTER;
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use axum::Json;
@@ -6,6 +7,8 @@ use serde_json::json;
 use tracing::{debug, error};
 use validator::ValidationErrors;
 
+use crate::model::CatalogEntry;
+
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
 #[derive(thiserror::Error, Debug, Serialize)]
@@ -21,10 +24,22 @@ pub enum ClientError {
     InvalidBearerToken,
     #[error("Authentication required")]
     AuthRequired,
+    #[error("Admin token required")]
+    AdminRequired,
     #[error("OpenID rejected")]
     OpenIDRejected,
     #[error("Not enough battles")]
     NotEnoughBattles,
+    #[error("Unrecognized {0:?}")]
+    Unrecognized(Vec<CatalogEntry>),
+    #[error("Rate limited, retry after {retry_after} seconds")]
+    RateLimited { retry_after: u64 },
+    #[error("Catalog entry already exists")]
+    CatalogEntryExists,
+    #[error("Unknown time zone: {0}")]
+    UnknownTimeZone(String),
+    #[error("Unknown server: {0}")]
+    UnknownServer(String),
 }
 
 impl ClientError {
@@ -35,8 +50,14 @@ impl ClientError {
             Self::ExpectedBearerToken => StatusCode::UNAUTHORIZED,
             Self::InvalidBearerToken => StatusCode::UNAUTHORIZED,
             Self::AuthRequired => StatusCode::UNAUTHORIZED,
+            Self::AdminRequired => StatusCode::FORBIDDEN,
             Self::OpenIDRejected => StatusCode::UNAUTHORIZED,
             Self::NotEnoughBattles => StatusCode::UNAUTHORIZED,
+            Self::Unrecognized(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+            Self::CatalogEntryExists => StatusCode::CONFLICT,
+            Self::UnknownTimeZone(_) => StatusCode::BAD_REQUEST,
+            Self::UnknownServer(_) => StatusCode::BAD_REQUEST,
         }
     }
 }
@@ -59,6 +80,11 @@ impl IntoResponse for Error {
             ),
         };
         let mut response = (status, Json(body)).into_response();
+        if let Self::ClientError(ClientError::RateLimited { retry_after }) = &self {
+            response
+                .headers_mut()
+                .insert(RETRY_AFTER, (*retry_after).into());
+        }
         response.extensions_mut().insert(self);
         response
     }
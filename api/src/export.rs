@@ -0,0 +1,130 @@
+use anyhow::{anyhow, Context, Error};
+use axum::body::{Bytes, StreamBody};
+use axum::http::header::CONTENT_TYPE;
+use axum::response::{IntoResponse, Response};
+use futures::channel::mpsc;
+use futures::{SinkExt, Stream, TryStreamExt};
+use serde::Serialize;
+use tracing::{debug, error};
+
+use crate::error::Result;
+
+// encoded rows are sent in chunks of at least this many bytes
+pub const CHUNK_SIZE: usize = 64 * 1024;
+
+// number of chunks which may be queued up, before fetching rows is paused
+const CHUNK_BUFFER: usize = 4;
+
+pub type ExportSender = mpsc::Sender<Result<Bytes, Error>>;
+
+pub type ExportReceiver = mpsc::Receiver<Result<Bytes, Error>>;
+
+#[derive(Debug, Clone, Copy)]
+pub enum ExportFormat {
+    Csv,
+    Ndjson,
+}
+
+impl ExportFormat {
+    fn content_type(&self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Ndjson => "application/x-ndjson",
+        }
+    }
+}
+
+pub struct Encoder {
+    format: ExportFormat,
+    buffer: Vec<u8>,
+    has_rows: bool,
+}
+
+impl Encoder {
+    pub fn new(format: ExportFormat) -> Self {
+        Self {
+            format,
+            buffer: Vec::new(),
+            has_rows: false,
+        }
+    }
+
+    pub fn encode<T: Serialize>(&mut self, row: &T) -> Result<()> {
+        match self.format {
+            ExportFormat::Csv => {
+                let mut writer = csv::WriterBuilder::new()
+                    .has_headers(!self.has_rows)
+                    .from_writer(&mut self.buffer);
+                writer.serialize(row).context("Failed to encode CSV row")?;
+                writer.flush().context("Failed to encode CSV row")?;
+            }
+            ExportFormat::Ndjson => {
+                serde_json::to_writer(&mut self.buffer, row)
+                    .context("Failed to encode JSON row")?;
+                self.buffer.push(b'\n');
+            }
+        }
+        self.has_rows = true;
+        Ok(())
+    }
+
+    pub fn take_chunk(&mut self, min_size: usize) -> Option<Bytes> {
+        if self.buffer.is_empty() || self.buffer.len() < min_size {
+            return None;
+        }
+        Some(Bytes::from(std::mem::take(&mut self.buffer)))
+    }
+}
+
+pub fn channel() -> (ExportSender, ExportReceiver) {
+    mpsc::channel(CHUNK_BUFFER)
+}
+
+pub fn response(format: ExportFormat, receiver: ExportReceiver) -> Response {
+    (
+        [(CONTENT_TYPE, format.content_type())],
+        StreamBody::new(receiver),
+    )
+        .into_response()
+}
+
+pub async fn send_rows<T, S>(format: ExportFormat, rows: S, mut sender: ExportSender)
+where
+    T: Serialize,
+    S: Stream<Item = sqlx::Result<T>> + Unpin,
+{
+    if let Err(e) = encode_rows(format, rows, &mut sender).await {
+        error!("{:?}", e);
+        // the status code is sent already, failing the body is the only way left to signal errors
+        sender.send(Err(anyhow!("Export failed"))).await.ok();
+    }
+}
+
+async fn encode_rows<T, S>(
+    format: ExportFormat,
+    mut rows: S,
+    sender: &mut ExportSender,
+) -> Result<()>
+where
+    T: Serialize,
+    S: Stream<Item = sqlx::Result<T>> + Unpin,
+{
+    let mut encoder = Encoder::new(format);
+    while let Some(row) = rows
+        .try_next()
+        .await
+        .context("Failed to fetch export row")?
+    {
+        encoder.encode(&row)?;
+        if let Some(chunk) = encoder.take_chunk(CHUNK_SIZE) {
+            if sender.send(Ok(chunk)).await.is_err() {
+                debug!("Export aborted by client.");
+                return Ok(());
+            }
+        }
+    }
+    if let Some(chunk) = encoder.take_chunk(0) {
+        sender.send(Ok(chunk)).await.ok();
+    }
+    Ok(())
+}
@@ -1,12 +1,14 @@
 use std::env;
 use std::net::{Ipv4Addr, SocketAddr};
+use std::path::Path;
 use std::time::Duration;
 
-use anyhow::Context;
+use anyhow::{anyhow, Context};
 use axum::extract::FromRef;
 use axum::http::HeaderValue;
 use axum::response::Response;
 use axum::{middleware, Router, Server};
+use chrono::Utc;
 use dotenvy::dotenv;
 use sqlx::postgres::PgPoolOptions;
 use sqlx::PgPool;
@@ -17,11 +19,22 @@ use tracing::{info, Level, Span};
 use util::request_id::{make_request_span, UuidRequestId, X_REQUEST_ID};
 
 use crate::auth::auth_middleware;
+use crate::current_maps::CurrentMapsHub;
 use crate::error::{log_embedded_errors, Result};
+use crate::notify::PlayedMapNotifier;
+use crate::rate_limit::RateLimiter;
 
+mod aggregate;
 mod auth;
+mod current_maps;
+mod dataset;
 mod error;
+mod export;
 mod model;
+mod notify;
+mod rate_limit;
+mod retention;
+mod rotation;
 mod router;
 mod service;
 mod util;
@@ -32,6 +45,17 @@ async fn main() -> Result<()> {
 
     tracing_subscriber::fmt::init();
 
+    let mut args = env::args().skip(1);
+    match args.next().as_deref() {
+        Some("dump-dataset") => {
+            let directory = args.next().context("Usage: api dump-dataset <directory>")?;
+            let pool = connect_database().await?;
+            return dataset::dump_dataset(&pool, Path::new(&directory)).await;
+        }
+        Some(command) => Err(anyhow!("Unknown command `{}`.", command))?,
+        None => {}
+    }
+
     info!("Initializing app context.");
     let app_context = init_app_context()
         .await
@@ -43,6 +67,43 @@ async fn main() -> Result<()> {
         .await
         .context("Database migration failed.")?;
 
+    let retention_days = util::env_var_or("RETENTION_DAYS", 90)?;
+
+    info!("Applying data retention policies.");
+    retention::apply_retention_policies(&app_context.pool, retention_days)
+        .await
+        .context("Failed to apply data retention policies.")?;
+
+    info!("Refreshing continuous aggregates.");
+    aggregate::refresh_played_map_minute(
+        &app_context.pool,
+        Utc::now() - chrono::Duration::days(retention_days.into()),
+    )
+    .await
+    .context("Failed to refresh continuous aggregates.")?;
+
+    tokio::spawn(retention::delete_expired_rows(
+        app_context.pool.clone(),
+        retention_days,
+    ));
+
+    tokio::spawn(notify::listen_played_maps(
+        app_context.pool.clone(),
+        app_context.played_map_notifier.clone(),
+    ));
+
+    let rotation_event_interval_minutes = util::env_var_or("ROTATION_EVENT_INTERVAL_MINUTES", 15)?;
+    if rotation_event_interval_minutes == 0 {
+        Err(anyhow!(
+            "Env var `ROTATION_EVENT_INTERVAL_MINUTES` must be greater than 0."
+        ))?;
+    }
+    let rotation_event_interval = Duration::from_secs(rotation_event_interval_minutes * 60);
+    tokio::spawn(rotation::detect_rotation_events(
+        app_context.pool.clone(),
+        rotation_event_interval,
+    ));
+
     let app = configure_app(app_context);
     let addr = SocketAddr::from((Ipv4Addr::UNSPECIFIED, 8080));
 
@@ -61,11 +122,18 @@ pub struct AppId(pub String);
 #[derive(Debug, Clone)]
 pub struct ServerSecret(pub String);
 
+#[derive(Debug, Clone)]
+pub struct AdminToken(pub Option<String>);
+
 #[derive(Clone, FromRef)]
 pub struct AppContext {
     pub pool: PgPool,
     pub app_id: AppId,
     pub server_secret: ServerSecret,
+    pub admin_token: AdminToken,
+    pub rate_limiter: RateLimiter,
+    pub played_map_notifier: PlayedMapNotifier,
+    pub current_maps_hub: CurrentMapsHub,
 }
 
 async fn init_app_context() -> Result<AppContext> {
@@ -77,6 +145,48 @@ async fn init_app_context() -> Result<AppContext> {
         .map(ServerSecret)
         .context("Env var `SERVER_SECRET` is not set.")?;
 
+    // an empty token would be matched by an empty header
+    let admin_token = AdminToken(
+        env::var("ADMIN_TOKEN")
+            .ok()
+            .filter(|token| !token.is_empty()),
+    );
+
+    // a burst of 0 rejects every report, a refill interval of 0 disables rate limiting
+    let rate_limit_burst = util::env_var_or("RATE_LIMIT_BURST", 100)?;
+    if rate_limit_burst == 0 {
+        Err(anyhow!(
+            "Env var `RATE_LIMIT_BURST` must be greater than 0."
+        ))?;
+    }
+    let rate_limit_refill_seconds = util::env_var_or("RATE_LIMIT_REFILL_SECONDS", 30)?;
+    if rate_limit_refill_seconds == 0 {
+        Err(anyhow!(
+            "Env var `RATE_LIMIT_REFILL_SECONDS` must be greater than 0."
+        ))?;
+    }
+    let rate_limiter = RateLimiter::new(
+        rate_limit_burst,
+        Duration::from_secs(rate_limit_refill_seconds),
+    );
+
+    let pool = connect_database().await?;
+
+    let played_map_notifier = PlayedMapNotifier::default();
+    let current_maps_hub = CurrentMapsHub::new(pool.clone(), played_map_notifier.clone());
+
+    Ok(AppContext {
+        pool,
+        app_id,
+        server_secret,
+        admin_token,
+        rate_limiter,
+        played_map_notifier,
+        current_maps_hub,
+    })
+}
+
+async fn connect_database() -> Result<PgPool> {
     let db_connection_str =
         env::var("DATABASE_URL").context("Env var `DATABASE_URL` is not set.")?;
 
@@ -90,11 +200,7 @@ async fn init_app_context() -> Result<AppContext> {
     .await
     .context("Cannot connect to database.")?;
 
-    Ok(AppContext {
-        pool,
-        app_id,
-        server_secret,
-    })
+    Ok(pool)
 }
 
 fn configure_app(app_context: AppContext) -> Router {
@@ -1,11 +1,14 @@
-use std::collections::HashMap;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
+use chrono::serde::{ts_seconds, ts_seconds_option};
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
 use validator::{Validate, ValidationError};
 
-use crate::error::Result;
+use crate::error::{ClientError, Result};
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Serialize, Validate)]
 #[validate(schema(function = "validate_tier_spread"))]
 pub struct ReportPlayedMapBody {
     #[validate(length(max = 10))]
@@ -18,6 +21,10 @@ pub struct ReportPlayedMapBody {
     pub bottom_tier: i16,
     #[validate(range(min = 1, max = 10))]
     pub top_tier: i16,
+    #[validate(range(min = 1))]
+    pub battle_id: Option<i64>,
+    #[serde(default, with = "ts_seconds_option")]
+    pub battle_time: Option<DateTime<Utc>>,
 }
 
 fn validate_tier_spread(body: &ReportPlayedMapBody) -> Result<(), ValidationError> {
@@ -28,45 +35,196 @@ fn validate_tier_spread(body: &ReportPlayedMapBody) -> Result<(), ValidationErro
     Ok(())
 }
 
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CatalogEntry {
+    Server,
+    Map,
+    Mode,
+}
+
+impl CatalogEntry {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Server => "server",
+            Self::Map => "map",
+            Self::Mode => "mode",
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Validate)]
+#[serde(transparent)]
+pub struct ReportPlayedMapsBody {
+    #[validate(length(min = 1, max = 100))]
+    pub reports: Vec<ReportPlayedMapBody>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "status")]
+pub enum ReportPlayedMapResult {
+    Accepted,
+    Rejected(ClientError),
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReportPlayedMapsResponse {
+    pub results: Vec<ReportPlayedMapResult>,
+}
+
+// maximum delay in minutes between a battle and its report, for the report to be considered plausible
+pub const MAX_REPORT_DELAY: i32 = 24 * 60;
+
+// default aggregation window in minutes
+pub const DEFAULT_WINDOW: i32 = 60;
+
+// window in minutes of the historical prior that current map counts are smoothed with
+pub const PRIOR_WINDOW: i32 = 7 * 24 * 60;
+
+// weight of the historical prior, in number of observations
+pub const PRIOR_WEIGHT: f64 = 10.0;
+
+// minimum interval in seconds between updates of streamed current maps
+pub const CURRENT_MAPS_STREAM_THROTTLE_SECONDS: u64 = 5;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Validate)]
 #[validate(schema(function = "validate_max_tier_goe_min_tier"))]
+#[validate(schema(function = "validate_server_xor_region"))]
 pub struct GetCurrentMapsQuery {
+    // comma-separated list of server names
+    #[validate(length(max = 50))]
+    pub server: Option<String>,
     #[validate(length(max = 10))]
-    pub server: String,
+    pub region: Option<String>,
     #[validate(range(min = 1, max = 10))]
     pub min_tier: i16,
     #[validate(range(min = 1, max = 10))]
     pub max_tier: i16,
+    #[validate(range(min = 10, max = 1440))]
+    pub window: Option<i32>,
+}
+
+impl GetCurrentMapsQuery {
+    pub fn servers(&self) -> Vec<String> {
+        self.server
+            .iter()
+            .flat_map(|servers| servers.split(','))
+            .map(|server| server.trim().to_string())
+            .collect()
+    }
+
+    // equivalent queries are equal once normalized
+    pub fn normalized(&self) -> Self {
+        let mut servers = self.servers();
+        servers.sort();
+        servers.dedup();
+        Self {
+            server: self.server.as_ref().map(|_| servers.join(",")),
+            region: self.region.clone(),
+            min_tier: self.min_tier,
+            max_tier: self.max_tier,
+            window: Some(self.window.unwrap_or(DEFAULT_WINDOW)),
+        }
+    }
 }
 
 fn validate_max_tier_goe_min_tier(payload: &GetCurrentMapsQuery) -> Result<(), ValidationError> {
-    if payload.min_tier > payload.max_tier {
+    validate_tier_range(payload.min_tier, payload.max_tier)
+}
+
+fn validate_server_xor_region(payload: &GetCurrentMapsQuery) -> Result<(), ValidationError> {
+    if payload.server.is_some() == payload.region.is_some() {
+        Err(ValidationError::new("Expected either server or region."))?;
+    }
+    Ok(())
+}
+
+fn validate_tier_range(min_tier: i16, max_tier: i16) -> Result<(), ValidationError> {
+    if min_tier > max_tier {
         Err(ValidationError::new("Invalid tier range."))?;
     }
     Ok(())
 }
 
+#[derive(Debug, Deserialize, Validate)]
+pub struct ReportActiveModesBody {
+    #[validate(length(max = 10))]
+    pub modes: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct CurrentMapRow {
+    pub map: String,
+    pub mode: String,
+    pub count: Option<i64>,
+    pub normalized: Option<f64>,
+    pub probability: Option<f64>,
+    pub reporters: Option<i64>,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct CurrentMap {
     pub map: String,
     pub mode: String,
     pub count: Option<i64>,
+    pub normalized: Option<f64>,
+    pub probability: Option<f64>,
+    pub share: Option<f64>,
+    pub share_interval: Option<(f64, f64)>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct CurrentMaps {
+    pub reporters: i64,
     pub modes: HashMap<String, Vec<CurrentMap>>,
 }
 
 impl CurrentMaps {
-    pub fn from_rows(rows: Vec<CurrentMap>) -> Self {
+    pub fn from_rows(rows: Vec<CurrentMapRow>) -> Self {
+        let reporters = rows.first().and_then(|row| row.reporters).unwrap_or(0);
         let mut modes = HashMap::new();
         rows.into_iter().for_each(|row| {
+            let count = row.count.unwrap_or(0);
             let maps = modes.entry(row.mode.clone()).or_insert_with(Vec::new);
-            maps.push(row);
+            maps.push(CurrentMap {
+                map: row.map,
+                mode: row.mode,
+                count: row.count,
+                normalized: row.normalized,
+                probability: row.probability,
+                share: (reporters > 0).then(|| count as f64 / reporters as f64),
+                share_interval: wilson_interval(count, reporters),
+            });
         });
-        Self { modes }
+        Self { reporters, modes }
+    }
+}
+
+// z-score of the 95% confidence level
+const WILSON_Z: f64 = 1.96;
+
+fn wilson_interval(successes: i64, trials: i64) -> Option<(f64, f64)> {
+    if trials == 0 {
+        return None;
     }
+    let n = trials as f64;
+    let p = successes as f64 / n;
+    let z2 = WILSON_Z * WILSON_Z;
+    let denominator = 1.0 + z2 / n;
+    let center = (p + z2 / (2.0 * n)) / denominator;
+    let margin = WILSON_Z / denominator * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt();
+    Some((center - margin, center + margin))
+}
+
+#[derive(Debug, Serialize)]
+pub struct CurrentMapsByServer {
+    pub servers: HashMap<String, CurrentMaps>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct GetCurrentServersQuery {
+    #[validate(range(min = 10, max = 1440))]
+    pub window: Option<i32>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -92,7 +250,899 @@ impl CurrentServers {
     }
 }
 
+pub const MAX_HISTORY_BUCKETS: i64 = 500;
+
+pub const DEFAULT_PAGE_SIZE: i64 = 50;
+
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_map_history_range"))]
+pub struct GetMapHistoryQuery {
+    #[validate(length(max = 10))]
+    pub server: String,
+    #[validate(range(min = 1, max = 10))]
+    pub min_tier: i16,
+    #[validate(range(min = 1, max = 10))]
+    pub max_tier: i16,
+    // bucket size in minutes
+    #[validate(range(min = 10, max = 10080))]
+    pub bucket: i32,
+    pub from: DateTime<Utc>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl GetMapHistoryQuery {
+    pub fn to(&self) -> DateTime<Utc> {
+        self.to.unwrap_or_else(Utc::now)
+    }
+}
+
+fn validate_map_history_range(payload: &GetMapHistoryQuery) -> Result<(), ValidationError> {
+    validate_tier_range(payload.min_tier, payload.max_tier)?;
+
+    let range = payload.to() - payload.from;
+    if range <= Duration::zero() {
+        Err(ValidationError::new("Invalid time range."))?;
+    }
+    if range.num_minutes() / payload.bucket as i64 >= MAX_HISTORY_BUCKETS {
+        Err(ValidationError::new("Too many buckets."))?;
+    }
+    Ok(())
+}
+
+#[derive(Debug)]
+pub struct MapHistoryRow {
+    pub time: DateTime<Utc>,
+    pub map: String,
+    pub mode: String,
+    pub count: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MapHistoryPoint {
+    pub time: DateTime<Utc>,
+    pub count: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MapHistorySeries {
+    pub map: String,
+    pub mode: String,
+    pub points: Vec<MapHistoryPoint>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MapHistory {
+    pub series: Vec<MapHistorySeries>,
+}
+
+impl MapHistory {
+    // expects rows ordered by map and mode
+    pub fn from_rows(rows: Vec<MapHistoryRow>) -> Self {
+        let mut series: Vec<MapHistorySeries> = Vec::new();
+        rows.into_iter().for_each(|row| {
+            let point = MapHistoryPoint {
+                time: row.time,
+                count: row.count,
+            };
+            match series.last_mut() {
+                Some(last) if last.map == row.map && last.mode == row.mode => {
+                    last.points.push(point)
+                }
+                _ => series.push(MapHistorySeries {
+                    map: row.map,
+                    mode: row.mode,
+                    points: vec![point],
+                }),
+            }
+        });
+        Self { series }
+    }
+}
+
+// time range in days of statistics, if not given explicitly
+pub const DEFAULT_STATISTICS_RANGE: i64 = 28;
+
+pub const MAX_STATISTICS_RANGE: i64 = 92;
+
+fn validate_statistics_range(
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<(), ValidationError> {
+    let range = to - from;
+    if range <= Duration::zero() {
+        Err(ValidationError::new("Invalid time range."))?;
+    }
+    if range > Duration::days(MAX_STATISTICS_RANGE) {
+        Err(ValidationError::new("Time range too large."))?;
+    }
+    Ok(())
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DistributionSlot {
+    Hour,
+    Weekday,
+    #[default]
+    WeekdayHour,
+}
+
+impl DistributionSlot {
+    pub fn by_weekday(&self) -> bool {
+        matches!(self, Self::Weekday | Self::WeekdayHour)
+    }
+
+    pub fn by_hour(&self) -> bool {
+        matches!(self, Self::Hour | Self::WeekdayHour)
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_map_distribution_range"))]
+pub struct GetMapDistributionQuery {
+    #[validate(length(max = 10))]
+    pub server: String,
+    #[validate(range(min = 1, max = 10))]
+    pub min_tier: i16,
+    #[validate(range(min = 1, max = 10))]
+    pub max_tier: i16,
+    #[validate(length(max = 64))]
+    pub time_zone: Option<String>,
+    #[serde(default)]
+    pub slot: DistributionSlot,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl GetMapDistributionQuery {
+    pub fn time_zone(&self) -> &str {
+        self.time_zone.as_deref().unwrap_or("UTC")
+    }
+
+    pub fn from(&self) -> DateTime<Utc> {
+        self.from
+            .unwrap_or_else(|| self.to() - Duration::days(DEFAULT_STATISTICS_RANGE))
+    }
+
+    pub fn to(&self) -> DateTime<Utc> {
+        self.to.unwrap_or_else(Utc::now)
+    }
+}
+
+fn validate_map_distribution_range(
+    payload: &GetMapDistributionQuery,
+) -> Result<(), ValidationError> {
+    validate_tier_range(payload.min_tier, payload.max_tier)?;
+    validate_statistics_range(payload.from(), payload.to())
+}
+
+#[derive(Debug)]
+pub struct MapDistributionRow {
+    pub weekday: Option<i16>,
+    pub hour: Option<i16>,
+    pub map: String,
+    pub count: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MapDistributionEntry {
+    pub map: String,
+    pub count: i64,
+    pub share: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MapDistributionSlot {
+    // ISO weekday, monday is 1
+    pub weekday: Option<i16>,
+    pub hour: Option<i16>,
+    pub count: i64,
+    pub maps: Vec<MapDistributionEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MapDistribution {
+    pub time_zone: String,
+    pub slots: Vec<MapDistributionSlot>,
+}
+
+impl MapDistribution {
+    // expects rows ordered by weekday and hour
+    pub fn from_rows(time_zone: String, rows: Vec<MapDistributionRow>) -> Self {
+        let mut slots: Vec<MapDistributionSlot> = Vec::new();
+        rows.into_iter().for_each(|row| {
+            let entry = MapDistributionEntry {
+                map: row.map,
+                count: row.count,
+                share: 0.0,
+            };
+            match slots.last_mut() {
+                Some(last) if last.weekday == row.weekday && last.hour == row.hour => {
+                    last.maps.push(entry)
+                }
+                _ => slots.push(MapDistributionSlot {
+                    weekday: row.weekday,
+                    hour: row.hour,
+                    count: 0,
+                    maps: vec![entry],
+                }),
+            }
+        });
+        for slot in &mut slots {
+            slot.count = slot.maps.iter().map(|map| map.count).sum();
+            for map in &mut slot.maps {
+                map.share = map.count as f64 / slot.count as f64;
+            }
+        }
+        Self { time_zone, slots }
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_mode_shares_range"))]
+pub struct GetModeSharesQuery {
+    #[validate(length(max = 10))]
+    pub server: String,
+    #[validate(range(min = 1, max = 10))]
+    pub min_tier: i16,
+    #[validate(range(min = 1, max = 10))]
+    pub max_tier: i16,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl GetModeSharesQuery {
+    pub fn from(&self) -> DateTime<Utc> {
+        self.from
+            .unwrap_or_else(|| self.to() - Duration::days(DEFAULT_STATISTICS_RANGE))
+    }
+
+    pub fn to(&self) -> DateTime<Utc> {
+        self.to.unwrap_or_else(Utc::now)
+    }
+}
+
+fn validate_mode_shares_range(payload: &GetModeSharesQuery) -> Result<(), ValidationError> {
+    validate_tier_range(payload.min_tier, payload.max_tier)?;
+    validate_statistics_range(payload.from(), payload.to())
+}
+
+#[derive(Debug)]
+pub struct ModeShareRow {
+    // `None` if the reporters did not report their enabled modes
+    pub enabled_modes: Option<Vec<String>>,
+    pub mode: String,
+    pub count: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModeShare {
+    pub mode: String,
+    pub count: i64,
+    pub share: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModeShares {
+    pub count: i64,
+    pub modes: Vec<ModeShare>,
+}
+
+type ModeCounts = Vec<(String, i64)>;
+
+impl ModeShares {
+    fn from_counts(counts: ModeCounts) -> Self {
+        let count = counts.iter().map(|(_, count)| count).sum();
+        let mut modes: Vec<_> = counts
+            .into_iter()
+            .map(|(mode, mode_count)| ModeShare {
+                mode,
+                count: mode_count,
+                share: mode_count as f64 / count as f64,
+            })
+            .collect();
+        modes.sort_by_key(|mode| Reverse(mode.count));
+        Self { count, modes }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct EnabledModeShares {
+    pub enabled_modes: Vec<String>,
+    #[serde(flatten)]
+    pub shares: ModeShares,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModeStatistics {
+    #[serde(flatten)]
+    pub shares: ModeShares,
+    pub by_enabled_modes: Vec<EnabledModeShares>,
+}
+
+impl ModeStatistics {
+    pub fn from_rows(rows: Vec<ModeShareRow>) -> Self {
+        let mut totals: BTreeMap<String, i64> = BTreeMap::new();
+        let mut by_enabled_modes: BTreeMap<Vec<String>, ModeCounts> = BTreeMap::new();
+        for row in rows {
+            *totals.entry(row.mode.clone()).or_default() += row.count;
+            if let Some(enabled_modes) = row.enabled_modes {
+                by_enabled_modes
+                    .entry(enabled_modes)
+                    .or_default()
+                    .push((row.mode, row.count));
+            }
+        }
+        Self {
+            shares: ModeShares::from_counts(totals.into_iter().collect()),
+            by_enabled_modes: by_enabled_modes
+                .into_iter()
+                .map(|(enabled_modes, counts)| EnabledModeShares {
+                    enabled_modes,
+                    shares: ModeShares::from_counts(counts),
+                })
+                .collect(),
+        }
+    }
+}
+
+// bucket size in minutes of exported map counts, if not given explicitly
+pub const DEFAULT_EXPORT_BUCKET: i32 = 60;
+
+// minimum number of distinct reporters of exported buckets
+pub const MIN_EXPORT_REPORTERS: i64 = 5;
+
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_export_map_counts_range"))]
+pub struct ExportMapCountsQuery {
+    #[validate(length(max = 10))]
+    pub server: Option<String>,
+    #[validate(range(min = 1, max = 10))]
+    pub min_tier: Option<i16>,
+    #[validate(range(min = 1, max = 10))]
+    pub max_tier: Option<i16>,
+    // bucket size in minutes
+    #[validate(range(min = 10, max = 10080))]
+    pub bucket: Option<i32>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl ExportMapCountsQuery {
+    pub fn min_tier(&self) -> i16 {
+        self.min_tier.unwrap_or(1)
+    }
+
+    pub fn max_tier(&self) -> i16 {
+        self.max_tier.unwrap_or(10)
+    }
+
+    pub fn bucket(&self) -> i32 {
+        self.bucket.unwrap_or(DEFAULT_EXPORT_BUCKET)
+    }
+
+    pub fn from(&self) -> DateTime<Utc> {
+        self.from
+            .unwrap_or_else(|| self.to() - Duration::days(DEFAULT_STATISTICS_RANGE))
+    }
+
+    pub fn to(&self) -> DateTime<Utc> {
+        self.to.unwrap_or_else(Utc::now)
+    }
+}
+
+fn validate_export_map_counts_range(payload: &ExportMapCountsQuery) -> Result<(), ValidationError> {
+    validate_tier_range(payload.min_tier(), payload.max_tier())?;
+    validate_statistics_range(payload.from(), payload.to())
+}
+
+#[derive(Debug, Serialize)]
+pub struct MapCountRow {
+    pub time: DateTime<Utc>,
+    pub server: String,
+    pub bottom_tier: i16,
+    pub top_tier: i16,
+    pub map: String,
+    pub mode: String,
+    pub count: i64,
+    pub reporters: i64,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct GetMapBracketsQuery {
+    #[validate(length(max = 10))]
+    pub server: String,
+    #[validate(range(min = 10, max = 1440))]
+    pub window: Option<i32>,
+}
+
+#[derive(Debug)]
+pub struct MapBracketRow {
+    pub map: String,
+    pub bottom_tier: i16,
+    pub top_tier: i16,
+    pub count: Option<i64>,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Bracket {
+    pub bottom_tier: i16,
+    pub top_tier: i16,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MapBracketCounts {
+    pub map: String,
+    pub counts: Vec<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MapBrackets {
+    pub brackets: Vec<Bracket>,
+    pub maps: Vec<MapBracketCounts>,
+}
+
+impl MapBrackets {
+    // expects rows ordered by map
+    pub fn from_rows(rows: Vec<MapBracketRow>) -> Self {
+        let brackets = rows
+            .iter()
+            .map(|row| Bracket {
+                bottom_tier: row.bottom_tier,
+                top_tier: row.top_tier,
+            })
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+
+        let mut maps: Vec<MapBracketCounts> = Vec::new();
+        rows.into_iter().for_each(|row| {
+            let bracket = Bracket {
+                bottom_tier: row.bottom_tier,
+                top_tier: row.top_tier,
+            };
+            let Ok(index) = brackets.binary_search(&bracket) else {
+                return;
+            };
+            let count = row.count.unwrap_or(0);
+            match maps.last_mut() {
+                Some(last) if last.map == row.map => last.counts[index] = count,
+                _ => {
+                    let mut counts = vec![0; brackets.len()];
+                    counts[index] = count;
+                    maps.push(MapBracketCounts {
+                        map: row.map,
+                        counts,
+                    });
+                }
+            }
+        });
+        Self { brackets, maps }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct PlayedMapCursor {
+    pub time: DateTime<Utc>,
+    pub id: i64,
+}
+
+impl TryFrom<String> for PlayedMapCursor {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let invalid = || format!("Invalid cursor: {}", value);
+        let (micros, id) = value.split_once('_').ok_or_else(invalid)?;
+
+        let micros = micros.parse::<i64>().map_err(|_| invalid())?;
+        let id = id.parse::<i64>().map_err(|_| invalid())?;
+        let time = NaiveDateTime::from_timestamp_opt(
+            micros.div_euclid(1_000_000),
+            micros.rem_euclid(1_000_000) as u32 * 1000,
+        )
+        .ok_or_else(invalid)?;
+
+        Ok(Self {
+            time: DateTime::from_utc(time, Utc),
+            id,
+        })
+    }
+}
+
+impl From<PlayedMapCursor> for String {
+    fn from(cursor: PlayedMapCursor) -> Self {
+        format!("{}_{}", cursor.time.timestamp_micros(), cursor.id)
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct GetMyPlayedMapsQuery {
+    #[validate(length(max = 10))]
+    pub server: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub cursor: Option<PlayedMapCursor>,
+    #[validate(range(min = 1, max = 100))]
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MyPlayedMap {
+    pub time: DateTime<Utc>,
+    pub server: String,
+    pub map: String,
+    pub mode: String,
+    pub bottom_tier: i16,
+    pub top_tier: i16,
+    pub implausible: bool,
+    #[serde(skip)]
+    pub id: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MyPlayedMaps {
+    pub played_maps: Vec<MyPlayedMap>,
+    pub next_cursor: Option<PlayedMapCursor>,
+}
+
+impl MyPlayedMaps {
+    // expects one row more than the page size, if there is a next page
+    pub fn from_rows(mut rows: Vec<MyPlayedMap>, limit: usize) -> Self {
+        let next_cursor = if rows.len() > limit {
+            rows.truncate(limit);
+            rows.last().map(|row| PlayedMapCursor {
+                time: row.time,
+                id: row.id,
+            })
+        } else {
+            None
+        };
+        Self {
+            played_maps: rows,
+            next_cursor,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct BlockedMapBody {
+    #[validate(length(max = 50))]
+    pub map: String,
+    #[serde(with = "ts_seconds")]
+    pub blocked_until: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ReportBlockedMapsBody {
+    #[validate(length(max = 20))]
+    #[validate]
+    pub maps: Vec<BlockedMapBody>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_blocked_maps_tier_range"))]
+pub struct GetBlockedMapsQuery {
+    #[validate(length(max = 10))]
+    pub server: String,
+    #[validate(range(min = 1, max = 10))]
+    pub min_tier: i16,
+    #[validate(range(min = 1, max = 10))]
+    pub max_tier: i16,
+}
+
+fn validate_blocked_maps_tier_range(payload: &GetBlockedMapsQuery) -> Result<(), ValidationError> {
+    validate_tier_range(payload.min_tier, payload.max_tier)
+}
+
+#[derive(Debug, Serialize)]
+pub struct BlockedMap {
+    pub map: String,
+    pub count: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BlockedMaps {
+    pub maps: Vec<BlockedMap>,
+}
+
+// window in minutes in which catalog maps without any reports are looked for
+pub const DEFAULT_ABSENT_WINDOW: i32 = 24 * 60;
+
+// minimum number of reporters before maps are considered likely absent
+pub const MIN_ABSENT_REPORTERS: i64 = 10;
+
+// maximum probability of a map in rotation having no reports, for it to be considered likely absent
+pub const MAX_ABSENT_MISS_PROBABILITY: f64 = 0.01;
+
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_absent_maps_tier_range"))]
+pub struct GetAbsentMapsQuery {
+    #[validate(length(max = 10))]
+    pub server: String,
+    #[validate(range(min = 1, max = 10))]
+    pub min_tier: i16,
+    #[validate(range(min = 1, max = 10))]
+    pub max_tier: i16,
+    #[validate(range(min = 60, max = 20160))]
+    pub window: Option<i32>,
+}
+
+fn validate_absent_maps_tier_range(payload: &GetAbsentMapsQuery) -> Result<(), ValidationError> {
+    validate_tier_range(payload.min_tier, payload.max_tier)
+}
+
+#[derive(Debug)]
+pub struct AbsentMapRow {
+    pub map: Option<String>,
+    pub reporters: i64,
+    pub reports: i64,
+    pub reported_maps: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AbsentMaps {
+    pub reporters: i64,
+    pub reports: i64,
+    // chance that any one map in rotation got no reports, the same for all maps
+    pub miss_probability: f64,
+    // whether the reports cover the bracket well enough to trust the list as a whole,
+    // not a verdict on individual maps
+    pub likely_absent: bool,
+    pub maps: Vec<String>,
+}
+
+impl AbsentMaps {
+    pub fn from_rows(rows: Vec<AbsentMapRow>) -> Self {
+        let (reporters, reports, reported_maps) = rows
+            .first()
+            .map(|row| (row.reporters, row.reports, row.reported_maps))
+            .unwrap_or_default();
+
+        // chance that a map in rotation got no reports at all, if it were as likely as the
+        // reported ones
+        let share = 1.0 / (reported_maps + 1) as f64;
+        let miss_probability = (1.0 - share).powf(reports as f64);
+
+        Self {
+            reporters,
+            reports,
+            miss_probability,
+            likely_absent: reporters >= MIN_ABSENT_REPORTERS
+                && miss_probability <= MAX_ABSENT_MISS_PROBABILITY,
+            maps: rows.into_iter().filter_map(|row| row.map).collect(),
+        }
+    }
+}
+
+// horizon in days over which the maps possible at each tier are inferred
+pub const DEFAULT_MAP_POOL_HORIZON: i32 = 90;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct GetMapPoolQuery {
+    #[validate(length(max = 10))]
+    pub server: Option<String>,
+    #[validate(range(min = 1, max = 365))]
+    pub horizon: Option<i32>,
+}
+
+#[derive(Debug)]
+pub struct MapPoolRow {
+    pub server: String,
+    pub tier: i16,
+    pub map: String,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    pub reports: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MapPoolEntry {
+    pub map: String,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    pub reports: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MapPool {
+    pub servers: HashMap<String, BTreeMap<i16, Vec<MapPoolEntry>>>,
+}
+
+impl MapPool {
+    pub fn from_rows(rows: Vec<MapPoolRow>) -> Self {
+        let mut servers: HashMap<String, BTreeMap<i16, Vec<MapPoolEntry>>> = HashMap::new();
+        for row in rows {
+            servers
+                .entry(row.server)
+                .or_default()
+                .entry(row.tier)
+                .or_default()
+                .push(MapPoolEntry {
+                    map: row.map,
+                    first_seen: row.first_seen,
+                    last_seen: row.last_seen,
+                    reports: row.reports,
+                });
+        }
+        Self { servers }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RotationEventKind {
+    Appeared,
+    Disappeared,
+    Shifted,
+}
+
+impl RotationEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Appeared => "appeared",
+            Self::Disappeared => "disappeared",
+            Self::Shifted => "shifted",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct GetRotationEventsQuery {
+    #[validate(length(max = 10))]
+    pub server: Option<String>,
+    pub kind: Option<RotationEventKind>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    #[validate(range(min = 1, max = 100))]
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RotationEvent {
+    pub time: DateTime<Utc>,
+    pub server: String,
+    pub map: String,
+    pub bottom_tier: i16,
+    pub top_tier: i16,
+    pub kind: String,
+    pub share: f64,
+    pub baseline_share: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RotationEvents {
+    pub events: Vec<RotationEvent>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PendingCatalogEntry {
+    pub entry: String,
+    pub code: String,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    pub reporter_count: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PendingCatalogEntries {
+    pub entries: Vec<PendingCatalogEntry>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_region_for_server"))]
+pub struct PromoteCatalogEntryBody {
+    pub entry: CatalogEntry,
+    #[validate(length(max = 50))]
+    pub code: String,
+    #[validate(range(min = 1))]
+    pub id: i16,
+    #[validate(length(max = 10))]
+    pub region: Option<String>,
+    #[serde(default)]
+    pub replay: bool,
+}
+
+fn validate_region_for_server(body: &PromoteCatalogEntryBody) -> Result<(), ValidationError> {
+    let is_server = matches!(body.entry, CatalogEntry::Server);
+    if is_server != body.region.is_some() {
+        Err(ValidationError::new("Region is required for servers only."))?;
+    }
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+pub struct PromoteCatalogEntryResponse {
+    pub replayed: u64,
+}
+
 #[derive(Debug, Serialize)]
 pub struct AuthenticateResponse {
     pub token: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn played_map(id: i64, time: DateTime<Utc>) -> MyPlayedMap {
+        MyPlayedMap {
+            time,
+            server: "EU1".to_string(),
+            map: "01_karelia".to_string(),
+            mode: "ctf".to_string(),
+            bottom_tier: 8,
+            top_tier: 10,
+            implausible: false,
+            id,
+        }
+    }
+
+    #[test]
+    fn played_map_cursor_round_trip() {
+        let cursor = PlayedMapCursor {
+            time: Utc.timestamp_opt(1_700_000_000, 123_456_000).unwrap(),
+            id: 42,
+        };
+
+        let encoded = String::from(cursor.clone());
+        assert_eq!(encoded, "1700000000123456_42");
+        assert_eq!(PlayedMapCursor::try_from(encoded), Ok(cursor));
+    }
+
+    #[test]
+    fn played_map_cursor_deserializes_from_string() {
+        let cursor: PlayedMapCursor = serde_json::from_str("\"1700000000123456_42\"").unwrap();
+        assert_eq!(cursor.id, 42);
+        assert_eq!(cursor.time.timestamp_micros(), 1_700_000_000_123_456);
+    }
+
+    #[test]
+    fn invalid_played_map_cursor() {
+        for value in [
+            "",
+            "42",
+            "_42",
+            "1700000000123456_",
+            "x_42",
+            "1700000000123456_1_2",
+        ] {
+            assert!(
+                PlayedMapCursor::try_from(value.to_string()).is_err(),
+                "{}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn my_played_maps_with_next_page() {
+        let time = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let rows = vec![
+            played_map(3, time),
+            played_map(2, time),
+            played_map(1, time),
+        ];
+
+        let page = MyPlayedMaps::from_rows(rows, 2);
+        let ids = page
+            .played_maps
+            .iter()
+            .map(|row| row.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, [3, 2]);
+        assert_eq!(page.next_cursor, Some(PlayedMapCursor { time, id: 2 }));
+    }
+
+    #[test]
+    fn my_played_maps_on_last_page() {
+        let time = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let rows = vec![played_map(2, time), played_map(1, time)];
+
+        let page = MyPlayedMaps::from_rows(rows, 2);
+        assert_eq!(page.played_maps.len(), 2);
+        assert_eq!(page.next_cursor, None);
+    }
+}
@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use sqlx::postgres::PgListener;
+use sqlx::PgPool;
+use tokio::sync::broadcast;
+use tracing::{error, info, warn};
+
+use crate::error::Result;
+
+// number of notifications a slow subscriber may fall behind, before it misses some
+const CHANNEL_CAPACITY: usize = 1024;
+
+// server and tiers of a played map
+#[derive(Debug, Clone)]
+pub struct PlayedMapNotification {
+    pub server: String,
+    pub bottom_tier: i16,
+    pub top_tier: i16,
+}
+
+impl PlayedMapNotification {
+    // parses payloads of the form `<server>:<bottom tier>:<top tier>`
+    fn parse(payload: &str) -> Option<Self> {
+        let mut parts = payload.rsplitn(3, ':');
+        let top_tier = parts.next()?.parse().ok()?;
+        let bottom_tier = parts.next()?.parse().ok()?;
+        let server = parts.next()?.to_string();
+        Some(Self {
+            server,
+            bottom_tier,
+            top_tier,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PlayedMapNotifier(broadcast::Sender<PlayedMapNotification>);
+
+impl Default for PlayedMapNotifier {
+    fn default() -> Self {
+        Self(broadcast::channel(CHANNEL_CAPACITY).0)
+    }
+}
+
+impl PlayedMapNotifier {
+    pub fn subscribe(&self) -> broadcast::Receiver<PlayedMapNotification> {
+        self.0.subscribe()
+    }
+}
+
+pub async fn listen_played_maps(pool: PgPool, notifier: PlayedMapNotifier) {
+    loop {
+        if let Err(e) = forward_notifications(&pool, &notifier).await {
+            error!("{:?}", e);
+        }
+        tokio::time::sleep(Duration::from_secs(5)).await;
+    }
+}
+
+async fn forward_notifications(pool: &PgPool, notifier: &PlayedMapNotifier) -> Result<()> {
+    let mut listener = PgListener::connect_with(pool)
+        .await
+        .context("Failed to connect listener")?;
+    listener
+        .listen("played_map")
+        .await
+        .context("Failed to listen for played maps")?;
+
+    info!("Listening for played maps.");
+    loop {
+        let notification = listener
+            .recv()
+            .await
+            .context("Failed to receive played map notification")?;
+        match PlayedMapNotification::parse(notification.payload()) {
+            // sending only fails if there are no subscribers
+            Some(played_map) => {
+                notifier.0.send(played_map).ok();
+            }
+            None => warn!(
+                "Ignoring malformed played map notification: {}",
+                notification.payload()
+            ),
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::async_trait;
+use axum::extract::{FromRef, FromRequestParts};
+use axum::http::request::Parts;
+
+use crate::auth::TokenClaims;
+use crate::error::{ClientError, Error, Result};
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+// In-memory token bucket per user. There are at most 16384 distinct users (see `create_token`),
+// so buckets are never evicted.
+#[derive(Clone)]
+pub struct RateLimiter {
+    burst: u32,
+    refill_interval: Duration,
+    buckets: Arc<Mutex<HashMap<String, Bucket>>>,
+}
+
+impl RateLimiter {
+    pub fn new(burst: u32, refill_interval: Duration) -> Self {
+        Self {
+            burst,
+            refill_interval,
+            buckets: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn acquire(&self, user_id: &str) -> Result<(), ClientError> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.entry(user_id.into()).or_insert_with(|| Bucket {
+            tokens: self.burst as f64,
+            updated: now,
+        });
+
+        let refilled =
+            now.duration_since(bucket.updated).as_secs_f64() / self.refill_interval.as_secs_f64();
+        bucket.tokens = (bucket.tokens + refilled).min(self.burst as f64);
+        bucket.updated = now;
+
+        if bucket.tokens < 1.0 {
+            let retry_after = (1.0 - bucket.tokens) * self.refill_interval.as_secs_f64();
+            return Err(ClientError::RateLimited {
+                retry_after: retry_after.ceil() as u64,
+            });
+        }
+
+        bucket.tokens -= 1.0;
+        Ok(())
+    }
+}
+
+pub struct RateLimit;
+
+#[async_trait]
+impl<S> FromRequestParts<S> for RateLimit
+where
+    RateLimiter: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let claims = TokenClaims::from_request_parts(parts, state).await?;
+        RateLimiter::from_ref(state).acquire(&claims.sub)?;
+        Ok(RateLimit)
+    }
+}
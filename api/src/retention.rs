@@ -0,0 +1,79 @@
+use std::time::Duration;
+
+use anyhow::{anyhow, Context};
+use sqlx::PgPool;
+use tracing::{error, info};
+
+use crate::error::Result;
+use crate::util;
+
+// interval in which expired rows of plain tables are deleted
+const EXPIRY_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+pub async fn apply_retention_policies(pool: &PgPool, retention_days: i32) -> Result<()> {
+    let compress_after_days = util::env_var_or("COMPRESS_AFTER_DAYS", 7)?;
+
+    // raw data must outlive the refresh window of `played_map_minute`, or it is lost there too
+    if retention_days <= 2 {
+        Err(anyhow!("Env var `RETENTION_DAYS` must be greater than 2."))?;
+    }
+
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+
+    // instances starting at the same time would otherwise add the same policies twice
+    sqlx::query_file!("queries/select_retention_policy_lock.sql")
+        .execute(&mut tx)
+        .await
+        .context("Failed to lock retention policies")?;
+
+    sqlx::query_file!("queries/remove_retention_policies.sql")
+        .fetch_one(&mut tx)
+        .await
+        .context("Failed to remove retention policies")?;
+
+    sqlx::query_file!(
+        "queries/add_retention_policies.sql",
+        compress_after_days,
+        retention_days
+    )
+    .fetch_one(&mut tx)
+    .await
+    .context("Failed to add retention policies")?;
+
+    tx.commit().await.context("Failed to commit policies")?;
+
+    Ok(())
+}
+
+// retention policies only apply to hypertables
+pub async fn delete_expired_rows(pool: PgPool, retention_days: i32) {
+    let mut interval = tokio::time::interval(EXPIRY_INTERVAL);
+    loop {
+        interval.tick().await;
+        match delete_expired(&pool, retention_days).await {
+            Ok(0) => {}
+            Ok(count) => info!("Deleted {} expired rows.", count),
+            Err(e) => error!("{:?}", e),
+        }
+    }
+}
+
+async fn delete_expired(pool: &PgPool, retention_days: i32) -> Result<u64> {
+    let played_battles =
+        sqlx::query_file!("queries/delete_expired_played_battles.sql", retention_days)
+            .execute(pool)
+            .await
+            .context("Failed to delete expired played battles")?
+            .rows_affected();
+
+    let quarantined_played_maps = sqlx::query_file!(
+        "queries/delete_expired_quarantined_played_maps.sql",
+        retention_days
+    )
+    .execute(pool)
+    .await
+    .context("Failed to delete expired quarantined played maps")?
+    .rows_affected();
+
+    Ok(played_battles + quarantined_played_maps)
+}
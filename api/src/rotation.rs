@@ -0,0 +1,61 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use sqlx::PgPool;
+use tracing::{error, info};
+
+use crate::error::Result;
+
+// window in minutes whose map shares are compared against the baseline
+const DETECTION_WINDOW: i32 = 6 * 60;
+
+// window in minutes preceding the detection window, which serves as baseline
+const BASELINE_WINDOW: i32 = 7 * 24 * 60;
+
+// minimum number of reports of a bracket in both windows
+const MIN_BRACKET_REPORTS: f64 = 100.0;
+
+// minimum number of standard errors by which a share has to differ from its baseline
+const MIN_Z_SCORE: f64 = 4.0;
+
+pub async fn detect_rotation_events(pool: PgPool, interval: Duration) {
+    let mut interval = tokio::time::interval(interval);
+    loop {
+        interval.tick().await;
+        match insert_rotation_events(&pool).await {
+            Ok(0) => {}
+            Ok(count) => info!("Detected {} rotation events.", count),
+            Err(e) => error!("{:?}", e),
+        }
+    }
+}
+
+async fn insert_rotation_events(pool: &PgPool) -> Result<usize> {
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+
+    // every instance runs the detection, the first one to get the lock does the work
+    let acquired = sqlx::query_file_scalar!("queries/select_rotation_event_lock.sql")
+        .fetch_one(&mut tx)
+        .await
+        .context("Failed to lock rotation events")?;
+    if !acquired {
+        return Ok(0);
+    }
+
+    let events = sqlx::query_file!(
+        "queries/insert_rotation_events.sql",
+        DETECTION_WINDOW,
+        BASELINE_WINDOW,
+        MIN_BRACKET_REPORTS,
+        MIN_Z_SCORE
+    )
+    .fetch_all(&mut tx)
+    .await
+    .context("Failed to insert rotation events")?;
+
+    tx.commit()
+        .await
+        .context("Failed to commit rotation events")?;
+
+    Ok(events.len())
+}
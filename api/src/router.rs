@@ -1,17 +1,38 @@
+use std::convert::Infallible;
+use std::slice;
+
 use anyhow::Context;
 use axum::extract::State;
 use axum::http::StatusCode;
-use axum::routing::{get, post};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::Response;
+use axum::routing::{get, post, put};
 use axum::{Json, Router};
-use sqlx::PgPool;
-use tracing::warn;
+use futures::{future, stream, Stream};
+use sqlx::{PgConnection, PgPool};
+use tracing::{debug, warn};
+use validator::Validate;
 
-use crate::auth::{create_token, TokenClaims};
-use crate::error::{ClientError, Result};
+use crate::aggregate;
+use crate::auth::{create_token, Admin, TokenClaims};
+use crate::current_maps::{select_current_maps, CurrentMapsHub};
+use crate::error::{ClientError, Error, Result};
+use crate::export::{self, ExportFormat};
 use crate::model::{
-    AuthenticateResponse, CurrentMap, CurrentMaps, CurrentServer, CurrentServers,
-    GetCurrentMapsQuery, ReportPlayedMapBody,
+    AbsentMapRow, AbsentMaps, AuthenticateResponse, BlockedMap, BlockedMaps, CatalogEntry,
+    CurrentMaps, CurrentMapsByServer, CurrentServer, CurrentServers, ExportMapCountsQuery,
+    GetAbsentMapsQuery, GetBlockedMapsQuery, GetCurrentMapsQuery, GetCurrentServersQuery,
+    GetMapBracketsQuery, GetMapDistributionQuery, GetMapHistoryQuery, GetMapPoolQuery,
+    GetModeSharesQuery, GetMyPlayedMapsQuery, GetRotationEventsQuery, MapBracketRow, MapBrackets,
+    MapCountRow, MapDistribution, MapDistributionRow, MapHistory, MapHistoryRow, MapPool,
+    MapPoolRow, ModeShareRow, ModeStatistics, MyPlayedMap, MyPlayedMaps, PendingCatalogEntries,
+    PendingCatalogEntry, PromoteCatalogEntryBody, PromoteCatalogEntryResponse,
+    ReportActiveModesBody, ReportBlockedMapsBody, ReportPlayedMapBody, ReportPlayedMapResult,
+    ReportPlayedMapsBody, ReportPlayedMapsResponse, RotationEvent, RotationEvents,
+    DEFAULT_ABSENT_WINDOW, DEFAULT_MAP_POOL_HORIZON, DEFAULT_PAGE_SIZE, DEFAULT_WINDOW,
+    MAX_REPORT_DELAY, MIN_EXPORT_REPORTERS,
 };
+use crate::rate_limit::{RateLimit, RateLimiter};
 use crate::service::api_client::ApiClient;
 use crate::service::openid_client::{OpenIDClient, OpenIDParams};
 use crate::util::validation::{ValidForm, ValidJson, ValidQuery};
@@ -20,35 +41,276 @@ use crate::{AppContext, AppId, ServerSecret};
 pub fn router() -> Router<AppContext> {
     Router::new()
         .route("/api/played-map", post(report_played_map))
+        .route("/api/played-maps", post(report_played_maps))
+        .route("/api/me/played-maps", get(get_my_played_maps))
+        .route(
+            "/api/blocked-maps",
+            put(report_blocked_maps).get(get_blocked_maps),
+        )
+        .route("/api/active-modes", put(report_active_modes))
         .route("/api/current-maps", get(get_current_maps))
+        .route(
+            "/api/current-maps/by-server",
+            get(get_current_maps_by_server),
+        )
+        .route("/api/current-maps/stream", get(stream_current_maps))
         .route("/api/current-servers", get(get_current_servers))
+        .route("/api/map-history", get(get_map_history))
+        .route("/api/map-brackets", get(get_map_brackets))
+        .route("/api/map-distribution", get(get_map_distribution))
+        .route("/api/mode-shares", get(get_mode_shares))
+        .route("/api/export/map-counts.csv", get(export_map_counts_csv))
+        .route(
+            "/api/export/map-counts.ndjson",
+            get(export_map_counts_ndjson),
+        )
+        .route("/api/absent-maps", get(get_absent_maps))
+        .route("/api/map-pool", get(get_map_pool))
+        .route("/api/events", get(get_rotation_events))
         .route("/api/authenticate", post(authenticate))
+        .route(
+            "/api/admin/pending-catalog-entries",
+            get(get_pending_catalog_entries),
+        )
+        .route("/api/admin/catalog-entries", post(promote_catalog_entry))
 }
 
 async fn report_played_map(
     State(pool): State<PgPool>,
     claims: TokenClaims,
+    _: RateLimit,
     ValidJson(body): ValidJson<ReportPlayedMapBody>,
 ) -> Result<StatusCode> {
+    let mut conn = pool
+        .acquire()
+        .await
+        .context("Failed to acquire connection")?;
+
+    insert_played_map(&mut conn, &claims.sub, &body).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn report_played_maps(
+    State(pool): State<PgPool>,
+    State(rate_limiter): State<RateLimiter>,
+    claims: TokenClaims,
+    ValidJson(body): ValidJson<ReportPlayedMapsBody>,
+) -> Result<Json<ReportPlayedMapsResponse>> {
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+
+    let mut results = Vec::with_capacity(body.reports.len());
+    for report in body.reports {
+        if let Err(e) = report.validate() {
+            results.push(ReportPlayedMapResult::Rejected(ClientError::Invalid(e)));
+            continue;
+        }
+
+        if let Err(e) = rate_limiter.acquire(&claims.sub) {
+            results.push(ReportPlayedMapResult::Rejected(e));
+            continue;
+        }
+
+        let result = match insert_played_map(&mut tx, &claims.sub, &report).await {
+            Ok(_) => ReportPlayedMapResult::Accepted,
+            Err(Error::ClientError(e)) => ReportPlayedMapResult::Rejected(e),
+            Err(e) => Err(e)?,
+        };
+        results.push(result);
+    }
+
+    tx.commit().await.context("Failed to commit played maps")?;
+
+    Ok(Json(ReportPlayedMapsResponse { results }))
+}
+
+// user IDs are pseudonyms, so this includes reports of other accounts sharing the same one
+async fn get_my_played_maps(
+    State(pool): State<PgPool>,
+    claims: TokenClaims,
+    ValidQuery(query): ValidQuery<GetMyPlayedMapsQuery>,
+) -> Result<Json<MyPlayedMaps>> {
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    let cursor = query.cursor.clone();
+
+    let rows = sqlx::query_file_as!(
+        MyPlayedMap,
+        "queries/select_my_played_maps.sql",
+        claims.sub,
+        query.server,
+        query.from,
+        query.to,
+        cursor.as_ref().map(|cursor| cursor.time),
+        cursor.as_ref().map(|cursor| cursor.id),
+        limit + 1
+    )
+    .fetch_all(&pool)
+    .await
+    .with_context(|| format!("Failed to select own played maps: {:?}", query))?;
+
+    Ok(Json(MyPlayedMaps::from_rows(rows, limit as usize)))
+}
+
+async fn insert_played_map(
+    conn: &mut PgConnection,
+    user_id: &str,
+    body: &ReportPlayedMapBody,
+) -> Result<()> {
     let row = sqlx::query_file!(
         "queries/insert_played_map.sql",
-        claims.sub,
+        user_id,
         body.server,
         body.map,
         body.mode,
         body.bottom_tier,
-        body.top_tier
+        body.top_tier,
+        body.battle_id,
+        body.battle_time,
+        MAX_REPORT_DELAY
     )
-    .fetch_optional(&pool)
+    .fetch_optional(&mut *conn)
     .await
     .with_context(|| format!("Failed to insert played map: {:?}", body))?;
 
-    if row.is_none() {
-        warn!(
-            "Unrecognized server, map, or mode: {}, {}, {}",
-            body.server, body.map, body.mode
+    if let Some(row) = row {
+        if row.implausible {
+            debug!("Implausible battle timing: {}, {:?}", user_id, body);
+        }
+        return Ok(());
+    }
+
+    let recognized = sqlx::query_file!(
+        "queries/select_recognized_played_map.sql",
+        body.server,
+        body.map,
+        body.mode
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .with_context(|| format!("Failed to check catalog entries: {:?}", body))?;
+
+    let unrecognized = [
+        (recognized.server, CatalogEntry::Server),
+        (recognized.map, CatalogEntry::Map),
+        (recognized.mode, CatalogEntry::Mode),
+    ]
+    .into_iter()
+    .filter_map(|(exists, entry)| (!exists).then_some(entry))
+    .collect::<Vec<_>>();
+
+    if unrecognized.is_empty() {
+        debug!("Duplicate battle: {}, {:?}", user_id, body.battle_id);
+        return Ok(());
+    }
+
+    warn!(
+        "Unrecognized server, map, or mode: {}, {}, {}",
+        body.server, body.map, body.mode
+    );
+    quarantine_played_map(conn, user_id, body, &unrecognized).await?;
+    Err(ClientError::Unrecognized(unrecognized).into())
+}
+
+async fn quarantine_played_map(
+    conn: &mut PgConnection,
+    user_id: &str,
+    body: &ReportPlayedMapBody,
+    unrecognized: &[CatalogEntry],
+) -> Result<()> {
+    sqlx::query_file!(
+        "queries/insert_quarantined_played_map.sql",
+        user_id,
+        body.server,
+        body.map,
+        body.mode,
+        body.bottom_tier,
+        body.top_tier,
+        body.battle_id,
+        body.battle_time
+    )
+    .execute(&mut *conn)
+    .await
+    .with_context(|| format!("Failed to quarantine played map: {:?}", body))?;
+
+    for entry in unrecognized {
+        let code = match entry {
+            CatalogEntry::Server => &body.server,
+            CatalogEntry::Map => &body.map,
+            CatalogEntry::Mode => &body.mode,
+        };
+
+        sqlx::query_file!(
+            "queries/upsert_pending_catalog_entry.sql",
+            entry.as_str(),
+            code
         )
+        .execute(&mut *conn)
+        .await
+        .with_context(|| format!("Failed to upsert pending {:?}: {}", entry, code))?;
     }
+    Ok(())
+}
+
+async fn report_blocked_maps(
+    State(pool): State<PgPool>,
+    claims: TokenClaims,
+    ValidJson(body): ValidJson<ReportBlockedMapsBody>,
+) -> Result<StatusCode> {
+    let (maps, blocked_until): (Vec<_>, Vec<_>) = body
+        .maps
+        .into_iter()
+        .map(|blocked_map| (blocked_map.map, blocked_map.blocked_until))
+        .unzip();
+
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+
+    sqlx::query_file!("queries/delete_blocked_maps.sql", claims.sub)
+        .execute(&mut tx)
+        .await
+        .context("Failed to delete blocked maps")?;
+
+    sqlx::query_file!(
+        "queries/insert_blocked_maps.sql",
+        claims.sub,
+        &maps,
+        &blocked_until
+    )
+    .execute(&mut tx)
+    .await
+    .with_context(|| format!("Failed to insert blocked maps: {:?}", maps))?;
+
+    tx.commit().await.context("Failed to commit blocked maps")?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn get_blocked_maps(
+    State(pool): State<PgPool>,
+    ValidQuery(query): ValidQuery<GetBlockedMapsQuery>,
+) -> Result<Json<BlockedMaps>> {
+    let maps = sqlx::query_file_as!(
+        BlockedMap,
+        "queries/select_blocked_maps.sql",
+        query.server,
+        query.min_tier,
+        query.max_tier
+    )
+    .fetch_all(&pool)
+    .await
+    .with_context(|| format!("Failed to select blocked maps: {:?}", query))?;
+
+    Ok(Json(BlockedMaps { maps }))
+}
+
+async fn report_active_modes(
+    State(pool): State<PgPool>,
+    claims: TokenClaims,
+    ValidJson(body): ValidJson<ReportActiveModesBody>,
+) -> Result<StatusCode> {
+    sqlx::query_file!("queries/insert_active_modes.sql", claims.sub, &body.modes)
+        .execute(&pool)
+        .await
+        .with_context(|| format!("Failed to insert active modes: {:?}", body))?;
+
     Ok(StatusCode::NO_CONTENT)
 }
 
@@ -56,27 +318,342 @@ async fn get_current_maps(
     State(pool): State<PgPool>,
     ValidQuery(query): ValidQuery<GetCurrentMapsQuery>,
 ) -> Result<Json<CurrentMaps>> {
+    let current_maps =
+        select_current_maps(&pool, &query.servers(), query.region.as_deref(), &query).await?;
+
+    Ok(Json(current_maps))
+}
+
+async fn get_current_maps_by_server(
+    State(pool): State<PgPool>,
+    ValidQuery(query): ValidQuery<GetCurrentMapsQuery>,
+) -> Result<Json<CurrentMapsByServer>> {
+    let names = sqlx::query_file_scalar!(
+        "queries/select_server_names.sql",
+        &query.servers(),
+        query.region
+    )
+    .fetch_all(&pool)
+    .await
+    .with_context(|| format!("Failed to select server names: {:?}", query))?;
+
+    let current_maps = future::try_join_all(
+        names
+            .iter()
+            .map(|name| select_current_maps(&pool, slice::from_ref(name), None, &query)),
+    )
+    .await?;
+    let servers = names.into_iter().zip(current_maps).collect();
+
+    Ok(Json(CurrentMapsByServer { servers }))
+}
+
+async fn stream_current_maps(
+    State(hub): State<CurrentMapsHub>,
+    ValidQuery(query): ValidQuery<GetCurrentMapsQuery>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let receiver = hub.subscribe(&query);
+
+    let stream = stream::unfold((receiver, true), |(mut receiver, initial)| async move {
+        if !initial {
+            receiver.changed().await.ok()?;
+        }
+        loop {
+            let event = receiver.borrow_and_update().clone();
+            if let Some(event) = event {
+                return Some((Ok(event), (receiver, false)));
+            }
+            receiver.changed().await.ok()?;
+        }
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+async fn get_current_servers(
+    State(pool): State<PgPool>,
+    ValidQuery(query): ValidQuery<GetCurrentServersQuery>,
+) -> Result<Json<CurrentServers>> {
     let rows = sqlx::query_file_as!(
-        CurrentMap,
-        "queries/select_current_maps.sql",
+        CurrentServer,
+        "queries/select_current_servers.sql",
+        query.window.unwrap_or(DEFAULT_WINDOW)
+    )
+    .fetch_all(&pool)
+    .await
+    .with_context(|| format!("Failed to select current servers: {:?}", query))?;
+
+    Ok(Json(CurrentServers::from_rows(rows)))
+}
+
+async fn get_map_history(
+    State(pool): State<PgPool>,
+    ValidQuery(query): ValidQuery<GetMapHistoryQuery>,
+) -> Result<Json<MapHistory>> {
+    let rows = sqlx::query_file_as!(
+        MapHistoryRow,
+        "queries/select_map_history.sql",
         query.server,
         query.min_tier,
-        query.max_tier
+        query.max_tier,
+        query.bucket,
+        query.from,
+        query.to()
+    )
+    .fetch_all(&pool)
+    .await
+    .with_context(|| format!("Failed to select map history: {:?}", query))?;
+
+    Ok(Json(MapHistory::from_rows(rows)))
+}
+
+async fn get_map_distribution(
+    State(pool): State<PgPool>,
+    ValidQuery(query): ValidQuery<GetMapDistributionQuery>,
+) -> Result<Json<MapDistribution>> {
+    let time_zone_exists =
+        sqlx::query_file_scalar!("queries/select_time_zone_exists.sql", query.time_zone())
+            .fetch_one(&pool)
+            .await
+            .context("Failed to look up time zone")?;
+
+    if !time_zone_exists {
+        Err(ClientError::UnknownTimeZone(query.time_zone().to_string()))?;
+    }
+
+    let rows = sqlx::query_file_as!(
+        MapDistributionRow,
+        "queries/select_map_distribution.sql",
+        query.server,
+        query.min_tier,
+        query.max_tier,
+        query.time_zone(),
+        query.from(),
+        query.to(),
+        query.slot.by_weekday(),
+        query.slot.by_hour()
+    )
+    .fetch_all(&pool)
+    .await
+    .with_context(|| format!("Failed to select map distribution: {:?}", query))?;
+
+    Ok(Json(MapDistribution::from_rows(
+        query.time_zone().to_string(),
+        rows,
+    )))
+}
+
+async fn get_mode_shares(
+    State(pool): State<PgPool>,
+    ValidQuery(query): ValidQuery<GetModeSharesQuery>,
+) -> Result<Json<ModeStatistics>> {
+    let rows = sqlx::query_file_as!(
+        ModeShareRow,
+        "queries/select_mode_shares.sql",
+        query.server,
+        query.min_tier,
+        query.max_tier,
+        query.from(),
+        query.to()
     )
     .fetch_all(&pool)
     .await
-    .with_context(|| format!("Failed to select current maps: {:?}", query))?;
+    .with_context(|| format!("Failed to select mode shares: {:?}", query))?;
+
+    Ok(Json(ModeStatistics::from_rows(rows)))
+}
+
+async fn export_map_counts_csv(
+    State(pool): State<PgPool>,
+    ValidQuery(query): ValidQuery<ExportMapCountsQuery>,
+) -> Response {
+    export_map_counts(pool, query, ExportFormat::Csv)
+}
 
-    Ok(Json(CurrentMaps::from_rows(rows)))
+async fn export_map_counts_ndjson(
+    State(pool): State<PgPool>,
+    ValidQuery(query): ValidQuery<ExportMapCountsQuery>,
+) -> Response {
+    export_map_counts(pool, query, ExportFormat::Ndjson)
 }
 
-async fn get_current_servers(State(pool): State<PgPool>) -> Result<Json<CurrentServers>> {
-    let rows = sqlx::query_file_as!(CurrentServer, "queries/select_current_servers.sql")
-        .fetch_all(&pool)
+fn export_map_counts(pool: PgPool, query: ExportMapCountsQuery, format: ExportFormat) -> Response {
+    let (sender, receiver) = export::channel();
+    tokio::spawn(async move {
+        let rows = sqlx::query_file_as!(
+            MapCountRow,
+            "queries/select_map_counts.sql",
+            query.bucket(),
+            query.server,
+            query.min_tier(),
+            query.max_tier(),
+            query.from(),
+            query.to(),
+            MIN_EXPORT_REPORTERS
+        )
+        .fetch(&pool);
+
+        export::send_rows(format, rows, sender).await;
+    });
+    export::response(format, receiver)
+}
+
+async fn get_map_brackets(
+    State(pool): State<PgPool>,
+    ValidQuery(query): ValidQuery<GetMapBracketsQuery>,
+) -> Result<Json<MapBrackets>> {
+    let rows = sqlx::query_file_as!(
+        MapBracketRow,
+        "queries/select_map_brackets.sql",
+        query.server,
+        query.window.unwrap_or(DEFAULT_WINDOW)
+    )
+    .fetch_all(&pool)
+    .await
+    .with_context(|| format!("Failed to select map brackets: {:?}", query))?;
+
+    Ok(Json(MapBrackets::from_rows(rows)))
+}
+
+async fn get_absent_maps(
+    State(pool): State<PgPool>,
+    ValidQuery(query): ValidQuery<GetAbsentMapsQuery>,
+) -> Result<Json<AbsentMaps>> {
+    // all maps would be absent on an unknown server
+    let server_id = sqlx::query_file_scalar!("queries/select_server_id.sql", query.server)
+        .fetch_optional(&pool)
         .await
-        .context("Failed to select current servers")?;
+        .context("Failed to look up server")?
+        .ok_or_else(|| ClientError::UnknownServer(query.server.clone()))?;
 
-    Ok(Json(CurrentServers::from_rows(rows)))
+    let rows = sqlx::query_file_as!(
+        AbsentMapRow,
+        "queries/select_absent_maps.sql",
+        server_id,
+        query.min_tier,
+        query.max_tier,
+        query.window.unwrap_or(DEFAULT_ABSENT_WINDOW)
+    )
+    .fetch_all(&pool)
+    .await
+    .with_context(|| format!("Failed to select absent maps: {:?}", query))?;
+
+    Ok(Json(AbsentMaps::from_rows(rows)))
+}
+
+async fn get_map_pool(
+    State(pool): State<PgPool>,
+    ValidQuery(query): ValidQuery<GetMapPoolQuery>,
+) -> Result<Json<MapPool>> {
+    let rows = sqlx::query_file_as!(
+        MapPoolRow,
+        "queries/select_map_pool.sql",
+        query.server,
+        query.horizon.unwrap_or(DEFAULT_MAP_POOL_HORIZON)
+    )
+    .fetch_all(&pool)
+    .await
+    .with_context(|| format!("Failed to select map pool: {:?}", query))?;
+
+    Ok(Json(MapPool::from_rows(rows)))
+}
+
+async fn get_rotation_events(
+    State(pool): State<PgPool>,
+    ValidQuery(query): ValidQuery<GetRotationEventsQuery>,
+) -> Result<Json<RotationEvents>> {
+    let events = sqlx::query_file_as!(
+        RotationEvent,
+        "queries/select_rotation_events.sql",
+        query.server,
+        query.kind.as_ref().map(|kind| kind.as_str()),
+        query.from,
+        query.to,
+        query.limit.unwrap_or(DEFAULT_PAGE_SIZE)
+    )
+    .fetch_all(&pool)
+    .await
+    .with_context(|| format!("Failed to select rotation events: {:?}", query))?;
+
+    Ok(Json(RotationEvents { events }))
+}
+
+async fn get_pending_catalog_entries(
+    _: Admin,
+    State(pool): State<PgPool>,
+) -> Result<Json<PendingCatalogEntries>> {
+    let entries = sqlx::query_file_as!(
+        PendingCatalogEntry,
+        "queries/select_pending_catalog_entries.sql"
+    )
+    .fetch_all(&pool)
+    .await
+    .context("Failed to select pending catalog entries")?;
+
+    Ok(Json(PendingCatalogEntries { entries }))
+}
+
+async fn promote_catalog_entry(
+    _: Admin,
+    State(pool): State<PgPool>,
+    ValidJson(body): ValidJson<PromoteCatalogEntryBody>,
+) -> Result<Json<PromoteCatalogEntryResponse>> {
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+
+    let row = match body.entry {
+        CatalogEntry::Server => {
+            sqlx::query_file_scalar!("queries/insert_server.sql", body.id, body.code, body.region)
+                .fetch_optional(&mut tx)
+                .await
+        }
+        CatalogEntry::Map => {
+            sqlx::query_file_scalar!("queries/insert_map.sql", body.id, body.code)
+                .fetch_optional(&mut tx)
+                .await
+        }
+        CatalogEntry::Mode => {
+            sqlx::query_file_scalar!("queries/insert_mode.sql", body.id, body.code)
+                .fetch_optional(&mut tx)
+                .await
+        }
+    }
+    .with_context(|| format!("Failed to insert catalog entry: {:?}", body))?;
+
+    if row.is_none() {
+        Err(ClientError::CatalogEntryExists)?;
+    }
+
+    sqlx::query_file!(
+        "queries/delete_pending_catalog_entry.sql",
+        body.entry.as_str(),
+        body.code
+    )
+    .execute(&mut tx)
+    .await
+    .with_context(|| format!("Failed to delete pending catalog entry: {:?}", body))?;
+
+    // replaying deletes the quarantined reports, without replay they are kept
+    let replayed = if body.replay {
+        sqlx::query_file_scalar!("queries/replay_quarantined_played_maps.sql")
+            .fetch_all(&mut tx)
+            .await
+            .with_context(|| format!("Failed to replay quarantined played maps: {:?}", body))?
+    } else {
+        Vec::new()
+    };
+
+    tx.commit()
+        .await
+        .context("Failed to commit catalog entry")?;
+
+    // replayed reports may be older than the buckets refreshed by the policy
+    if let Some(from) = replayed.iter().min() {
+        aggregate::refresh_played_map_minute(&pool, *from).await?;
+    }
+
+    Ok(Json(PromoteCatalogEntryResponse {
+        replayed: replayed.len() as u64,
+    }))
 }
 
 async fn authenticate(
@@ -1,8 +1,14 @@
+use std::env;
 use std::fmt::Display;
 use std::future::Future;
+use std::str::FromStr;
 use std::time::Duration;
+
+use anyhow::anyhow;
 use tracing::warn;
 
+use crate::error::Result as AppResult;
+
 pub mod request_id;
 pub mod validation;
 
@@ -27,3 +33,12 @@ pub async fn retry<T, E: Display, Fut: Future<Output = Result<T, E>>, F: Fn() ->
         }
     }
 }
+
+pub fn env_var_or<T: FromStr>(key: &str, default: T) -> AppResult<T> {
+    match env::var(key) {
+        Ok(value) => value
+            .parse()
+            .map_err(|_| anyhow!("Env var `{}` is invalid: {}", key, value).into()),
+        Err(_) => Ok(default),
+    }
+}
@@ -7,6 +7,8 @@ import {
   CurrentServers,
   ErrorResponse,
   GetCurrentMapsQuery,
+  ReportActiveModesBody,
+  ReportBlockedMapsBody,
   ReportPlayedMapBody,
 } from "./schema"
 
@@ -27,6 +29,8 @@ export const ApiResponseError = customError(
 
 export interface Api {
   reportPlayedMap(token: string, body: ReportPlayedMapBody): Promise<void>
+  reportBlockedMaps(token: string, body: ReportBlockedMapsBody): Promise<void>
+  reportActiveModes(token: string, body: ReportActiveModesBody): Promise<void>
   getCurrentMaps(query: GetCurrentMapsQuery): Promise<CurrentMaps>
   getCurrentServers(): Promise<CurrentServers>
   authenticate(params: FormData): Promise<AuthenticateResponse>
@@ -48,6 +52,36 @@ export function createApi(baseUrl: URL): Api {
     return expectNoResponse(res)
   }
 
+  async function reportBlockedMaps(token: string, body: ReportBlockedMapsBody) {
+    const url = new URL("/api/blocked-maps", baseUrl)
+    body = mask(body, ReportBlockedMapsBody)
+    const res = await fetch(url, {
+      method: "PUT",
+      headers: {
+        [Header.Authorization]: `Bearer ${token}`,
+        [Header.ContentType]: "application/json",
+        [Header.RequestId]: uuid(),
+      },
+      body: JSON.stringify(body),
+    })
+    return expectNoResponse(res)
+  }
+
+  async function reportActiveModes(token: string, body: ReportActiveModesBody) {
+    const url = new URL("/api/active-modes", baseUrl)
+    body = mask(body, ReportActiveModesBody)
+    const res = await fetch(url, {
+      method: "PUT",
+      headers: {
+        [Header.Authorization]: `Bearer ${token}`,
+        [Header.ContentType]: "application/json",
+        [Header.RequestId]: uuid(),
+      },
+      body: JSON.stringify(body),
+    })
+    return expectNoResponse(res)
+  }
+
   async function getCurrentMaps(query: GetCurrentMapsQuery) {
     const url = new URL("/api/current-maps", baseUrl)
     query = mask(query, GetCurrentMapsQuery)
@@ -78,7 +112,14 @@ export function createApi(baseUrl: URL): Api {
     return expectJsonResponse(res, AuthenticateResponse)
   }
 
-  return { reportPlayedMap, getCurrentMaps, getCurrentServers, authenticate }
+  return {
+    reportPlayedMap,
+    reportBlockedMaps,
+    reportActiveModes,
+    getCurrentMaps,
+    getCurrentServers,
+    authenticate,
+  }
 }
 
 async function expectJsonResponse<T>(res: Response, Type: Struct<T>) {
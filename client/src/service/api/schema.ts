@@ -1,4 +1,15 @@
-import { array, Infer, number, object, optional, record, string, unknown } from "superstruct"
+import {
+  array,
+  Infer,
+  nullable,
+  number,
+  object,
+  optional,
+  record,
+  string,
+  tuple,
+  unknown,
+} from "superstruct"
 
 export type ReportPlayedMapBody = Infer<typeof ReportPlayedMapBody>
 export const ReportPlayedMapBody = object({
@@ -7,6 +18,23 @@ export const ReportPlayedMapBody = object({
   mode: string(),
   bottom_tier: number(),
   top_tier: number(),
+  battle_id: optional(number()),
+  battle_time: optional(number()),
+})
+
+export type ReportBlockedMapsBody = Infer<typeof ReportBlockedMapsBody>
+export const ReportBlockedMapsBody = object({
+  maps: array(
+    object({
+      map: string(),
+      blocked_until: number(),
+    }),
+  ),
+})
+
+export type ReportActiveModesBody = Infer<typeof ReportActiveModesBody>
+export const ReportActiveModesBody = object({
+  modes: array(string()),
 })
 
 export type GetCurrentMapsQuery = Infer<typeof GetCurrentMapsQuery>
@@ -21,10 +49,15 @@ const CurrentMap = object({
   map: string(),
   mode: string(),
   count: number(),
+  normalized: nullable(number()),
+  probability: number(),
+  share: nullable(number()),
+  share_interval: nullable(tuple([number(), number()])),
 })
 
 export type CurrentMaps = Infer<typeof CurrentMaps>
 export const CurrentMaps = object({
+  reporters: number(),
   modes: record(string(), array(CurrentMap)),
 })
 
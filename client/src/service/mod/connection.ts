@@ -54,12 +54,20 @@ export function createModConnection(socket: WebSocket, api: Api, auth: Auth): Mo
     }
   }
 
-  function handleBlockedMaps(message: BlockedMaps) {
+  async function handleBlockedMaps(message: BlockedMaps) {
     setBlockedMaps(message.maps)
+    const currentToken = auth.token()
+    if (currentToken) {
+      await api.reportBlockedMaps(currentToken, message)
+    }
   }
 
-  function handleActiveModes(message: ActiveModes) {
+  async function handleActiveModes(message: ActiveModes) {
     setActiveModes(message.modes)
+    const currentToken = auth.token()
+    if (currentToken) {
+      await api.reportActiveModes(currentToken, message)
+    }
   }
 
   return { blockedMaps, activeModes }
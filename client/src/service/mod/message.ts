@@ -1,4 +1,4 @@
-import { array, Infer, literal, number, object, string, union } from "superstruct"
+import { array, Infer, literal, number, object, optional, string, union } from "superstruct"
 
 export const enum MessageType {
   Version = "Version",
@@ -21,6 +21,8 @@ const PlayedMap = object({
   mode: string(),
   bottom_tier: number(),
   top_tier: number(),
+  battle_id: optional(number()),
+  battle_time: optional(number()),
 })
 
 export type BlockedMap = Infer<typeof BlockedMap>